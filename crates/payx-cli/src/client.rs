@@ -31,6 +31,19 @@ impl ApiClient {
         self.handle_response(resp).await
     }
 
+    /// GETs a non-JSON resource and returns the body as text.
+    pub async fn get_text(&self, path: &str) -> Result<String> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut req = self.client.get(&url);
+
+        if let Some(key) = &self.api_key {
+            req = req.header("Authorization", format!("Bearer {}", key));
+        }
+
+        let resp = req.send().await.context("request failed")?;
+        self.read_body(resp).await
+    }
+
     pub async fn post<T: DeserializeOwned, B: Serialize>(&self, path: &str, body: &B) -> Result<T> {
         let url = format!("{}{}", self.base_url, path);
         let mut req = self.client.post(&url).json(body);
@@ -81,6 +94,11 @@ impl ApiClient {
     }
 
     async fn handle_response<T: DeserializeOwned>(&self, resp: reqwest::Response) -> Result<T> {
+        let body = self.read_body(resp).await?;
+        serde_json::from_str(&body).context("failed to parse response")
+    }
+
+    async fn read_body(&self, resp: reqwest::Response) -> Result<String> {
         let status = resp.status();
         let body = resp.text().await.context("failed to read response")?;
 
@@ -101,6 +119,6 @@ impl ApiClient {
             bail!("request failed with status {}: {}", status, body);
         }
 
-        serde_json::from_str(&body).context("failed to parse response")
+        Ok(body)
     }
 }
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::NaiveDate;
 use clap::Subcommand;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -15,6 +16,10 @@ pub enum Commands {
     List {
         #[arg(long, help = "Filter by business ID")]
         business_id: Option<Uuid>,
+        #[arg(long, help = "Filter by external ID")]
+        external_id: Option<String>,
+        #[arg(long, help = "List sub-accounts of this account")]
+        parent_account_id: Option<Uuid>,
         #[arg(long, default_value = "50")]
         limit: i64,
         #[arg(long)]
@@ -24,16 +29,74 @@ pub enum Commands {
     Create {
         #[arg(long)]
         business_id: Uuid,
+        #[arg(
+            long,
+            default_value = "liability",
+            help = "asset, liability, equity, revenue or expense"
+        )]
+        account_type: String,
         #[arg(long, default_value = "USD")]
         currency: String,
         #[arg(long, default_value = "0")]
         balance: Decimal,
+        #[arg(
+            long,
+            default_value = "0",
+            help = "How far the balance may go below zero"
+        )]
+        overdraft_limit: Decimal,
+        #[arg(long, help = "Your own reference, unique per business")]
+        external_id: Option<String>,
+        #[arg(long, help = "Display name")]
+        name: Option<String>,
+        #[arg(long, help = "Create as a sub-account of this account")]
+        parent_account_id: Option<Uuid>,
     },
     /// Get account details
     Get {
         #[arg(help = "Account ID")]
         id: Uuid,
     },
+    /// Freeze an account so it can receive but not send funds
+    Freeze {
+        #[arg(help = "Account ID")]
+        id: Uuid,
+    },
+    /// Unfreeze a frozen account
+    Unfreeze {
+        #[arg(help = "Account ID")]
+        id: Uuid,
+    },
+    /// Close an account with a zero balance
+    Close {
+        #[arg(help = "Account ID")]
+        id: Uuid,
+    },
+    /// Show the consolidated balance of an account and its sub-accounts
+    Rollup {
+        #[arg(help = "Account ID")]
+        id: Uuid,
+    },
+    /// Show daily closing balances
+    DailyBalances {
+        #[arg(help = "Account ID")]
+        id: Uuid,
+        #[arg(long, help = "First day (YYYY-MM-DD)")]
+        from: Option<NaiveDate>,
+        #[arg(long, help = "Last day (YYYY-MM-DD)")]
+        to: Option<NaiveDate>,
+    },
+    /// Show a statement with opening and closing balances
+    Statement {
+        #[arg(help = "Account ID")]
+        id: Uuid,
+        #[arg(long, help = "First day (YYYY-MM-DD)")]
+        from: NaiveDate,
+        #[arg(long, help = "Last day (YYYY-MM-DD)")]
+        to: NaiveDate,
+        #[arg(long, help = "Print the statement as CSV")]
+        csv: bool,
+    },
     /// List account transactions
     Transactions {
         #[arg(help = "Account ID")]
@@ -41,25 +104,47 @@ pub enum Commands {
         #[arg(long, default_value = "20")]
         limit: i64,
     },
+    /// List account ledger entries with running balances
+    Ledger {
+        #[arg(help = "Account ID")]
+        id: Uuid,
+        #[arg(long, default_value = "20")]
+        limit: i64,
+        #[arg(long, help = "Pagination cursor (last entry ID)")]
+        cursor: Option<Uuid>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct CreateRequest {
     business_id: Uuid,
+    account_type: String,
     currency: String,
     #[serde(with = "rust_decimal::serde::str")]
     initial_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    overdraft_limit: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_account_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Tabled)]
 pub struct Account {
     pub id: Uuid,
     pub business_id: Uuid,
+    pub account_type: String,
     pub currency: String,
     #[tabled(display_with = "display_decimal")]
     pub balance: Decimal,
     #[tabled(display_with = "display_decimal")]
     pub available_balance: Decimal,
+    #[tabled(display_with = "display_decimal")]
+    pub overdraft_limit: Decimal,
+    pub status: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Tabled)]
@@ -78,6 +163,64 @@ pub struct Transaction {
     pub currency: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+pub struct LedgerEntry {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub entry_type: String,
+    #[tabled(display_with = "display_decimal")]
+    pub amount: Decimal,
+    #[tabled(display_with = "display_decimal")]
+    pub balance_after: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+struct Rollup {
+    balances: Vec<RollupBalance>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+pub struct RollupBalance {
+    pub currency: String,
+    #[tabled(display_with = "display_decimal")]
+    pub balance: Decimal,
+    #[tabled(display_with = "display_decimal")]
+    pub available_balance: Decimal,
+    pub accounts: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+pub struct BalanceSnapshot {
+    pub snapshot_date: NaiveDate,
+    #[tabled(display_with = "display_decimal")]
+    pub balance: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Statement {
+    #[serde(with = "rust_decimal::serde::str")]
+    opening_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    total_credits: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    total_debits: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    closing_balance: Decimal,
+    lines: Vec<StatementLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+pub struct StatementLine {
+    pub created_at: String,
+    pub transaction_id: Uuid,
+    pub transaction_type: String,
+    pub entry_type: String,
+    #[tabled(display_with = "display_decimal")]
+    pub amount: Decimal,
+    #[tabled(display_with = "display_decimal")]
+    pub balance: Decimal,
+}
+
 fn display_decimal(d: &Decimal) -> String {
     d.to_string()
 }
@@ -92,6 +235,8 @@ pub async fn run(cmd: Commands, config: &Config, format: Format) -> Result<()> {
     match cmd {
         Commands::List {
             business_id,
+            external_id,
+            parent_account_id,
             limit,
             offset,
         } => {
@@ -99,6 +244,12 @@ pub async fn run(cmd: Commands, config: &Config, format: Format) -> Result<()> {
             if let Some(biz_id) = business_id {
                 url.push_str(&format!("&business_id={}", biz_id));
             }
+            if let Some(ext_id) = external_id {
+                url.push_str(&format!("&external_id={}", ext_id));
+            }
+            if let Some(parent_id) = parent_account_id {
+                url.push_str(&format!("&parent_account_id={}", parent_id));
+            }
             if let Some(off) = offset {
                 url.push_str(&format!("&offset={}", off));
             }
@@ -107,13 +258,23 @@ pub async fn run(cmd: Commands, config: &Config, format: Format) -> Result<()> {
         }
         Commands::Create {
             business_id,
+            account_type,
             currency,
             balance,
+            overdraft_limit,
+            external_id,
+            name,
+            parent_account_id,
         } => {
             let req = CreateRequest {
                 business_id,
+                account_type,
                 currency,
                 initial_balance: balance,
+                overdraft_limit,
+                external_id,
+                name,
+                parent_account_id,
             };
             let account: Account = client.post("/v1/accounts", &req).await?;
             output::print_created(account, format);
@@ -122,12 +283,76 @@ pub async fn run(cmd: Commands, config: &Config, format: Format) -> Result<()> {
             let account: Account = client.get(&format!("/v1/accounts/{}", id)).await?;
             output::print_item(account, format);
         }
+        Commands::Freeze { id } => {
+            let account: Account = client
+                .post(&format!("/v1/accounts/{}/freeze", id), &())
+                .await?;
+            output::print_item(account, format);
+        }
+        Commands::Unfreeze { id } => {
+            let account: Account = client
+                .post(&format!("/v1/accounts/{}/unfreeze", id), &())
+                .await?;
+            output::print_item(account, format);
+        }
+        Commands::Close { id } => {
+            let account: Account = client
+                .post(&format!("/v1/accounts/{}/close", id), &())
+                .await?;
+            output::print_item(account, format);
+        }
+        Commands::Rollup { id } => {
+            let rollup: Rollup = client.get(&format!("/v1/accounts/{}/rollup", id)).await?;
+            output::print_items(rollup.balances, format);
+        }
+        Commands::DailyBalances { id, from, to } => {
+            let mut params = Vec::new();
+            if let Some(from) = from {
+                params.push(format!("from={}", from));
+            }
+            if let Some(to) = to {
+                params.push(format!("to={}", to));
+            }
+            let mut url = format!("/v1/accounts/{}/balances/daily", id);
+            if !params.is_empty() {
+                url.push_str(&format!("?{}", params.join("&")));
+            }
+            let snapshots: Vec<BalanceSnapshot> = client.get(&url).await?;
+            output::print_items(snapshots, format);
+        }
+        Commands::Statement { id, from, to, csv } => {
+            let url = format!("/v1/accounts/{}/statement?from={}&to={}", id, from, to);
+            if csv {
+                print!("{}", client.get_text(&format!("{}&format=csv", url)).await?);
+                return Ok(());
+            }
+
+            let statement: Statement = client.get(&url).await?;
+            match format {
+                Format::Json => output::print_json(&statement),
+                Format::Table => {
+                    println!("Opening balance: {}", statement.opening_balance);
+                    output::print_items(statement.lines, format);
+                    println!("Total credits:   {}", statement.total_credits);
+                    println!("Total debits:    {}", statement.total_debits);
+                    println!("Closing balance: {}", statement.closing_balance);
+                }
+            }
+        }
         Commands::Transactions { id, limit } => {
             let txns: Vec<Transaction> = client
                 .get(&format!("/v1/accounts/{}/transactions?limit={}", id, limit))
                 .await?;
             output::print_items(txns, format);
         }
+        Commands::Ledger { id, limit, cursor } => {
+            let mut url = format!("/v1/accounts/{}/ledger?limit={}", id, limit);
+            if let Some(c) = cursor {
+                url.push_str(&format!("&cursor={}", c));
+            }
+            let entries: Vec<LedgerEntry> = client.get(&url).await?;
+            output::print_items(entries, format);
+        }
     }
 
     Ok(())
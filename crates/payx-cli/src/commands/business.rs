@@ -25,6 +25,8 @@ pub enum Commands {
         email: String,
         #[arg(long)]
         webhook_url: Option<String>,
+        #[arg(long, help = "IANA timezone daily balances close in (default: UTC)")]
+        timezone: Option<String>,
     },
     /// Get business details
     Get {
@@ -39,6 +41,8 @@ struct CreateRequest {
     email: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     webhook_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Tabled)]
@@ -48,6 +52,7 @@ pub struct Business {
     pub email: String,
     #[tabled(display_with = "display_option")]
     pub webhook_url: Option<String>,
+    pub timezone: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -84,11 +89,13 @@ pub async fn run(cmd: Commands, config: &Config, format: Format) -> Result<()> {
             name,
             email,
             webhook_url,
+            timezone,
         } => {
             let req = CreateRequest {
                 name,
                 email,
                 webhook_url,
+                timezone,
             };
             let resp: CreateResponse = client.post_no_auth("/v1/businesses", &req).await?;
 
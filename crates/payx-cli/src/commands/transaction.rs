@@ -66,6 +66,28 @@ pub enum Commands {
         #[arg(help = "Transaction ID")]
         id: Uuid,
     },
+    /// Reverse (refund) a completed transaction
+    Reverse {
+        #[arg(help = "Transaction ID")]
+        id: Uuid,
+        #[arg(long, help = "Partial refund amount (default: remaining amount)")]
+        amount: Option<Decimal>,
+        #[arg(long)]
+        description: Option<String>,
+        #[arg(long, help = "Idempotency key to prevent duplicates")]
+        idempotency_key: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct ReverseRequest {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "rust_decimal::serde::str_option"
+    )]
+    amount: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -191,6 +213,25 @@ pub async fn run(cmd: Commands, config: &Config, format: Format) -> Result<()> {
             let txn: Transaction = client.get(&format!("/v1/transactions/{}", id)).await?;
             output::print_item(txn, format);
         }
+        Commands::Reverse {
+            id,
+            amount,
+            description,
+            idempotency_key,
+        } => {
+            let req = ReverseRequest {
+                amount,
+                description,
+            };
+            let txn: Transaction = client
+                .post_with_idempotency(
+                    &format!("/v1/transactions/{}/reverse", id),
+                    &req,
+                    idempotency_key.as_deref(),
+                )
+                .await?;
+            output::print_created(txn, format);
+        }
     }
 
     Ok(())
@@ -0,0 +1,18 @@
+CREATE TABLE holds (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL UNIQUE REFERENCES transactions(id),
+    account_id UUID NOT NULL REFERENCES accounts(id),
+    destination_account_id UUID REFERENCES accounts(id),
+    amount DECIMAL(19, 4) NOT NULL,
+    captured_amount DECIMAL(19, 4),
+    currency VARCHAR(3) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'active',
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    released_at TIMESTAMPTZ,
+    CONSTRAINT positive_hold_amount CHECK (amount > 0),
+    CONSTRAINT captured_within_hold CHECK (captured_amount IS NULL OR (captured_amount > 0 AND captured_amount <= amount))
+);
+
+CREATE INDEX idx_holds_account ON holds(account_id);
+CREATE INDEX idx_holds_expiring ON holds(expires_at) WHERE status = 'active';
@@ -0,0 +1,3 @@
+ALTER TABLE transactions ADD COLUMN reversed_transaction_id UUID REFERENCES transactions(id);
+
+CREATE INDEX idx_transactions_reversed ON transactions(reversed_transaction_id) WHERE reversed_transaction_id IS NOT NULL;
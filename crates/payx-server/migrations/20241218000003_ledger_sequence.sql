@@ -0,0 +1,6 @@
+-- Strict per-account ordering for running balances and keyset pagination.
+-- Entries for one account are written under that account's row lock, so
+-- their sequence numbers increase in posting order.
+ALTER TABLE ledger_entries ADD COLUMN seq BIGSERIAL;
+
+CREATE UNIQUE INDEX idx_ledger_entries_account_seq ON ledger_entries(account_id, seq);
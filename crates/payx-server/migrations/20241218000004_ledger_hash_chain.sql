@@ -0,0 +1,5 @@
+-- Tamper-evident chain: each entry stores the hash of the previous entry on
+-- the same account alongside a hash of its own contents. Entries written
+-- before this migration stay unhashed and are skipped by verification.
+ALTER TABLE ledger_entries ADD COLUMN prev_hash VARCHAR(64);
+ALTER TABLE ledger_entries ADD COLUMN hash VARCHAR(64);
@@ -0,0 +1,10 @@
+-- Balances may go negative down to the account's overdraft limit.
+ALTER TABLE accounts ADD COLUMN overdraft_limit DECIMAL(19, 4) NOT NULL DEFAULT 0;
+
+ALTER TABLE accounts DROP CONSTRAINT positive_balance;
+ALTER TABLE accounts DROP CONSTRAINT positive_available;
+
+ALTER TABLE accounts
+    ADD CONSTRAINT non_negative_overdraft_limit CHECK (overdraft_limit >= 0),
+    ADD CONSTRAINT balance_within_overdraft CHECK (balance >= -overdraft_limit),
+    ADD CONSTRAINT available_within_overdraft CHECK (available_balance >= -overdraft_limit);
@@ -0,0 +1,57 @@
+-- Every business gets one settlement account per currency. It is the
+-- counterparty of credits and debits, so every transaction posts balanced
+-- entries. Settlement balances are unbounded: a negative balance is money
+-- that has entered the ledger from outside.
+CREATE UNIQUE INDEX idx_accounts_settlement ON accounts(business_id, currency)
+    WHERE account_type = 'settlement';
+
+ALTER TABLE accounts DROP CONSTRAINT balance_within_overdraft;
+ALTER TABLE accounts DROP CONSTRAINT available_within_overdraft;
+
+ALTER TABLE accounts
+    ADD CONSTRAINT balance_within_overdraft
+        CHECK (account_type = 'settlement' OR balance >= -overdraft_limit),
+    ADD CONSTRAINT available_within_overdraft
+        CHECK (account_type = 'settlement' OR available_balance >= -overdraft_limit);
+
+-- Backfill: post the missing counter-entry of every one-sided transaction
+-- written before settlement accounts existed.
+CREATE TEMPORARY TABLE unbalanced_transactions ON COMMIT DROP AS
+SELECT le.transaction_id,
+    (ARRAY_AGG(a.business_id))[1] AS business_id,
+    t.currency,
+    t.created_at,
+    SUM(CASE WHEN le.entry_type = 'credit' THEN le.amount ELSE -le.amount END) AS net
+FROM ledger_entries le
+JOIN accounts a ON a.id = le.account_id
+JOIN transactions t ON t.id = le.transaction_id
+GROUP BY le.transaction_id, t.currency, t.created_at
+HAVING SUM(CASE WHEN le.entry_type = 'credit' THEN le.amount ELSE -le.amount END) <> 0;
+
+INSERT INTO accounts (business_id, account_type, currency, balance, available_balance, version, created_at, updated_at)
+SELECT DISTINCT business_id, 'settlement', currency, 0, 0, 0, NOW(), NOW()
+FROM unbalanced_transactions;
+
+INSERT INTO ledger_entries (transaction_id, account_id, entry_type, amount, balance_after, created_at)
+SELECT u.transaction_id, s.id,
+    CASE WHEN u.net > 0 THEN 'debit' ELSE 'credit' END,
+    ABS(u.net),
+    -SUM(u.net) OVER (PARTITION BY s.id ORDER BY u.created_at, u.transaction_id),
+    u.created_at
+FROM unbalanced_transactions u
+JOIN accounts s ON s.business_id = u.business_id
+    AND s.currency = u.currency
+    AND s.account_type = 'settlement'
+ORDER BY u.created_at, u.transaction_id;
+
+UPDATE accounts s
+SET balance = totals.balance, available_balance = totals.balance
+FROM (
+    SELECT s.id, -SUM(u.net) AS balance
+    FROM unbalanced_transactions u
+    JOIN accounts s ON s.business_id = u.business_id
+        AND s.currency = u.currency
+        AND s.account_type = 'settlement'
+    GROUP BY s.id
+) totals
+WHERE s.id = totals.id;
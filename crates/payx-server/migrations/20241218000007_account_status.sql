@@ -0,0 +1,3 @@
+-- Frozen accounts can receive but not send funds; closed accounts do neither.
+ALTER TABLE accounts ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'active';
+ALTER TABLE accounts ADD COLUMN closed_at TIMESTAMPTZ;
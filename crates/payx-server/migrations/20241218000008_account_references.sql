@@ -0,0 +1,7 @@
+-- Client-supplied references so accounts can be looked up by the caller's own IDs.
+ALTER TABLE accounts ADD COLUMN external_id VARCHAR(255);
+ALTER TABLE accounts ADD COLUMN name VARCHAR(255);
+ALTER TABLE accounts ADD COLUMN metadata JSONB;
+
+CREATE UNIQUE INDEX idx_accounts_external_id ON accounts(business_id, external_id)
+    WHERE external_id IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Sub-accounts roll up into their parent. A parent is set at creation and
+-- belongs to the same business, so the hierarchy cannot contain cycles.
+ALTER TABLE accounts ADD COLUMN parent_account_id UUID REFERENCES accounts(id);
+
+CREATE INDEX idx_accounts_parent ON accounts(parent_account_id) WHERE parent_account_id IS NOT NULL;
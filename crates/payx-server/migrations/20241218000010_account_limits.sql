@@ -0,0 +1,14 @@
+-- Velocity limits on outgoing debits. A missing row or NULL column means
+-- the limit is not enforced. Windows are UTC calendar days and months.
+CREATE TABLE account_limits (
+    account_id UUID PRIMARY KEY REFERENCES accounts(id),
+    max_transaction_amount DECIMAL(19, 4) CHECK (max_transaction_amount > 0),
+    daily_debit_count INTEGER CHECK (daily_debit_count >= 0),
+    daily_debit_amount DECIMAL(19, 4) CHECK (daily_debit_amount >= 0),
+    monthly_debit_count INTEGER CHECK (monthly_debit_count >= 0),
+    monthly_debit_amount DECIMAL(19, 4) CHECK (monthly_debit_amount >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_ledger_entries_account_debits ON ledger_entries(account_id, created_at)
+    WHERE entry_type = 'debit';
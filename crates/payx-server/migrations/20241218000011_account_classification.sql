@@ -0,0 +1,76 @@
+-- account_type becomes a chart-of-accounts classification with a normal
+-- balance side: asset and expense accounts increase with debits; liability,
+-- equity and revenue accounts increase with credits. Balances are stored
+-- with the normal side positive.
+--
+-- Settlement accounts are identified by a flag and classified as assets.
+-- Until now their balances were kept credit-positive, so their balances and
+-- ledger history are negated and their hash chains rebuilt. Every other
+-- existing account held customer funds and becomes a liability, which keeps
+-- its balances unchanged.
+ALTER TABLE accounts ADD COLUMN is_settlement BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE accounts SET is_settlement = TRUE WHERE account_type = 'settlement';
+
+DROP INDEX idx_accounts_settlement;
+CREATE UNIQUE INDEX idx_accounts_settlement ON accounts(business_id, currency)
+    WHERE is_settlement;
+
+ALTER TABLE accounts DROP CONSTRAINT balance_within_overdraft;
+ALTER TABLE accounts DROP CONSTRAINT available_within_overdraft;
+
+UPDATE accounts
+SET account_type = 'asset', balance = -balance, available_balance = -available_balance
+WHERE is_settlement;
+
+UPDATE accounts SET account_type = 'liability' WHERE NOT is_settlement;
+
+ALTER TABLE accounts
+    ALTER COLUMN account_type SET DEFAULT 'liability',
+    ADD CONSTRAINT valid_account_type
+        CHECK (account_type IN ('asset', 'liability', 'equity', 'revenue', 'expense')),
+    ADD CONSTRAINT balance_within_overdraft
+        CHECK (is_settlement OR balance >= -overdraft_limit),
+    ADD CONSTRAINT available_within_overdraft
+        CHECK (is_settlement OR available_balance >= -overdraft_limit),
+    ADD COLUMN normal_balance VARCHAR(10) GENERATED ALWAYS AS (
+        CASE WHEN account_type IN ('asset', 'expense') THEN 'debit' ELSE 'credit' END
+    ) STORED;
+
+UPDATE ledger_entries SET balance_after = -balance_after
+WHERE account_id IN (SELECT id FROM accounts WHERE is_settlement);
+
+-- Rebuild the chains of the rewritten entries exactly as the application
+-- hashes them (see LedgerEntry::compute_hash).
+DO $$
+DECLARE
+    entry RECORD;
+    current_account UUID;
+    last_hash VARCHAR(64);
+    entry_hash VARCHAR(64);
+BEGIN
+    FOR entry IN
+        SELECT le.* FROM ledger_entries le
+        JOIN accounts a ON a.id = le.account_id
+        WHERE a.is_settlement AND le.hash IS NOT NULL
+        ORDER BY le.account_id, le.seq
+    LOOP
+        IF current_account IS DISTINCT FROM entry.account_id THEN
+            current_account := entry.account_id;
+            last_hash := entry.prev_hash;
+        END IF;
+
+        entry_hash := encode(sha256(convert_to(concat_ws('|',
+            last_hash,
+            entry.id::text,
+            entry.transaction_id::text,
+            entry.account_id::text,
+            entry.entry_type,
+            trim_scale(entry.amount)::text,
+            trim_scale(entry.balance_after)::text,
+            (EXTRACT(EPOCH FROM entry.created_at) * 1000000)::bigint::text
+        ), 'UTF8')), 'hex');
+
+        UPDATE ledger_entries SET prev_hash = last_hash, hash = entry_hash WHERE id = entry.id;
+        last_hash := entry_hash;
+    END LOOP;
+END $$;
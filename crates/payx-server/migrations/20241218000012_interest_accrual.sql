@@ -0,0 +1,27 @@
+-- Interest accrues daily on an account's end-of-day balance and is posted
+-- monthly as a credit funded by a business expense account.
+CREATE TABLE account_interest (
+    account_id UUID PRIMARY KEY REFERENCES accounts(id),
+    annual_rate DECIMAL(9, 6) NOT NULL CHECK (annual_rate >= 0),
+    expense_account_id UUID NOT NULL REFERENCES accounts(id),
+    -- Last day accrued; accrual resumes on the following day
+    accrued_through DATE NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE interest_accruals (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    account_id UUID NOT NULL REFERENCES accounts(id),
+    accrual_date DATE NOT NULL,
+    balance DECIMAL(19, 4) NOT NULL,
+    annual_rate DECIMAL(9, 6) NOT NULL,
+    amount DECIMAL(19, 8) NOT NULL,
+    transaction_id UUID REFERENCES transactions(id),
+    posted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (account_id, accrual_date)
+);
+
+CREATE INDEX idx_interest_accruals_unposted ON interest_accruals(account_id, accrual_date)
+    WHERE posted_at IS NULL;
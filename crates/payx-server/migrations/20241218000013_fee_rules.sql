@@ -0,0 +1,22 @@
+-- Fees a business charges on its credits, debits and transfers, at most one
+-- rule per transaction type and currency. Fees post as extra ledger legs
+-- into the rule's revenue account, and each transaction records the fees it
+-- was charged.
+CREATE TABLE fee_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    business_id UUID NOT NULL REFERENCES businesses(id),
+    transaction_type VARCHAR(20) NOT NULL
+        CHECK (transaction_type IN ('credit', 'debit', 'transfer')),
+    currency VARCHAR(3) NOT NULL,
+    flat_amount DECIMAL(19, 4) NOT NULL DEFAULT 0 CHECK (flat_amount >= 0),
+    percentage DECIMAL(9, 6) NOT NULL DEFAULT 0 CHECK (percentage >= 0 AND percentage <= 100),
+    min_amount DECIMAL(19, 4) CHECK (min_amount >= 0),
+    max_amount DECIMAL(19, 4) CHECK (max_amount >= 0),
+    revenue_account_id UUID NOT NULL REFERENCES accounts(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT fee_min_within_max CHECK (min_amount IS NULL OR max_amount IS NULL OR min_amount <= max_amount),
+    CONSTRAINT fee_rules_unique_type UNIQUE (business_id, transaction_type, currency)
+);
+
+ALTER TABLE transactions ADD COLUMN fees JSONB NOT NULL DEFAULT '[]';
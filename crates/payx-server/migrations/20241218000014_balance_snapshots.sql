@@ -0,0 +1,12 @@
+-- Closing balance of every account for each day, where days are calendar
+-- days in the business's timezone. Written by a background worker once a
+-- day has ended, so history can be read without scanning the ledger.
+ALTER TABLE businesses ADD COLUMN timezone VARCHAR(64) NOT NULL DEFAULT 'UTC';
+
+CREATE TABLE balance_snapshots (
+    account_id UUID NOT NULL REFERENCES accounts(id),
+    snapshot_date DATE NOT NULL,
+    balance DECIMAL(19, 4) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (account_id, snapshot_date)
+);
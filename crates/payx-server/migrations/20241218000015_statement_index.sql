@@ -0,0 +1,2 @@
+-- Statements read an account's entries by time range.
+CREATE INDEX idx_ledger_entries_account_created ON ledger_entries(account_id, created_at);
@@ -0,0 +1,123 @@
+-- Account opening balances used to be written straight into the balance
+-- columns. Backfill an `opening` transaction for every account whose balance
+-- is not explained by its ledger: the account's entry is dated at account
+-- creation and goes at the start of its chain, and the counter-entry is
+-- appended to the business's settlement account now.
+CREATE TEMPORARY TABLE openings ON COMMIT DROP AS
+SELECT gen_random_uuid() AS transaction_id,
+    gen_random_uuid() AS entry_id,
+    gen_random_uuid() AS settlement_entry_id,
+    a.id AS account_id,
+    a.business_id,
+    a.currency,
+    a.created_at,
+    o.opening,
+    CASE WHEN o.opening > 0 THEN a.normal_balance
+        WHEN a.normal_balance = 'debit' THEN 'credit'
+        ELSE 'debit'
+    END AS entry_type,
+    f.seq AS first_seq,
+    -- An opening entry ahead of unhashed entries stays unhashed with them
+    f.seq IS NULL OR f.hash IS NOT NULL AS hashed
+FROM accounts a
+LEFT JOIN LATERAL (
+    SELECT le.seq, le.hash, le.balance_after,
+        CASE WHEN le.entry_type = a.normal_balance THEN le.amount ELSE -le.amount END AS delta
+    FROM ledger_entries le
+    WHERE le.account_id = a.id
+    ORDER BY le.seq
+    LIMIT 1
+) f ON TRUE
+CROSS JOIN LATERAL (
+    SELECT COALESCE(f.balance_after - f.delta, a.balance) AS opening
+) o
+WHERE NOT a.is_settlement AND o.opening <> 0;
+
+INSERT INTO accounts (business_id, account_type, is_settlement, currency, balance, available_balance, version, created_at, updated_at)
+SELECT DISTINCT business_id, 'asset', TRUE, currency, 0, 0, 0, NOW(), NOW()
+FROM openings
+ON CONFLICT (business_id, currency) WHERE is_settlement DO NOTHING;
+
+INSERT INTO transactions (id, type, status, destination_account_id, amount, currency, description, created_at, completed_at)
+SELECT transaction_id, 'opening', 'completed', account_id, ABS(opening), currency, 'Opening balance', created_at, created_at
+FROM openings;
+
+-- The account's first entry has the lowest seq on the account, so the seq
+-- just below it is free.
+INSERT INTO ledger_entries (id, transaction_id, account_id, entry_type, amount, balance_after, created_at, seq)
+SELECT entry_id, transaction_id, account_id, entry_type, ABS(opening), opening, created_at,
+    COALESCE(first_seq - 1, nextval(pg_get_serial_sequence('ledger_entries', 'seq')))
+FROM openings;
+
+CREATE TEMPORARY TABLE settlement_openings ON COMMIT DROP AS
+SELECT o.settlement_entry_id AS entry_id, o.transaction_id, s.id AS account_id,
+    CASE WHEN o.entry_type = 'debit' THEN 'credit' ELSE 'debit' END AS entry_type,
+    ABS(o.opening) AS amount,
+    s.balance + SUM(CASE WHEN o.entry_type = 'debit' THEN -ABS(o.opening) ELSE ABS(o.opening) END)
+        OVER (PARTITION BY s.id ORDER BY o.created_at, o.transaction_id) AS balance_after,
+    o.created_at AS opened_at
+FROM openings o
+JOIN accounts s ON s.business_id = o.business_id
+    AND s.currency = o.currency
+    AND s.is_settlement;
+
+INSERT INTO ledger_entries (id, transaction_id, account_id, entry_type, amount, balance_after, created_at)
+SELECT entry_id, transaction_id, account_id, entry_type, amount, balance_after, NOW()
+FROM settlement_openings
+ORDER BY opened_at, transaction_id;
+
+UPDATE accounts s
+SET balance = totals.balance, available_balance = s.available_balance + totals.balance - s.balance
+FROM (
+    SELECT DISTINCT ON (account_id) account_id, balance_after AS balance
+    FROM settlement_openings
+    ORDER BY account_id, opened_at DESC, transaction_id DESC
+) totals
+WHERE s.id = totals.account_id;
+
+-- Hash the new entries and rebuild the chains they joined exactly as the
+-- application hashes them (see LedgerEntry::compute_hash).
+DO $$
+DECLARE
+    entry RECORD;
+    current_account UUID;
+    hashing BOOLEAN;
+    last_hash VARCHAR(64);
+    entry_hash VARCHAR(64);
+BEGIN
+    FOR entry IN
+        SELECT le.*,
+            le.id IN (SELECT entry_id FROM settlement_openings)
+                OR le.id IN (SELECT entry_id FROM openings WHERE hashed) AS is_new
+        FROM ledger_entries le
+        WHERE le.account_id IN (SELECT account_id FROM openings WHERE hashed)
+            OR le.account_id IN (SELECT account_id FROM settlement_openings)
+        ORDER BY le.account_id, le.seq
+    LOOP
+        IF current_account IS DISTINCT FROM entry.account_id THEN
+            current_account := entry.account_id;
+            hashing := FALSE;
+        END IF;
+
+        IF NOT hashing THEN
+            -- Unhashed entries written before hash chains stay unhashed
+            CONTINUE WHEN entry.hash IS NULL AND NOT entry.is_new;
+            hashing := TRUE;
+            last_hash := COALESCE(entry.prev_hash, repeat('0', 64));
+        END IF;
+
+        entry_hash := encode(sha256(convert_to(concat_ws('|',
+            last_hash,
+            entry.id::text,
+            entry.transaction_id::text,
+            entry.account_id::text,
+            entry.entry_type,
+            trim_scale(entry.amount)::text,
+            trim_scale(entry.balance_after)::text,
+            (EXTRACT(EPOCH FROM entry.created_at) * 1000000)::bigint::text
+        ), 'UTF8')), 'hex');
+
+        UPDATE ledger_entries SET prev_hash = last_hash, hash = entry_hash WHERE id = entry.id;
+        last_hash := entry_hash;
+    END LOOP;
+END $$;
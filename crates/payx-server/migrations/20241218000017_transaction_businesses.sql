@@ -0,0 +1,15 @@
+-- Transactions have no owner column; a business sees every transaction that
+-- touches one of its accounts, as a party or through a ledger entry. Pending
+-- hold transactions have no entries yet.
+CREATE VIEW transaction_businesses AS
+SELECT t.id AS transaction_id, a.business_id
+FROM transactions t
+JOIN accounts a ON a.id = t.source_account_id
+UNION
+SELECT t.id, a.business_id
+FROM transactions t
+JOIN accounts a ON a.id = t.destination_account_id
+UNION
+SELECT le.transaction_id, a.business_id
+FROM ledger_entries le
+JOIN accounts a ON a.id = le.account_id;
@@ -0,0 +1,12 @@
+-- Keys issued before scopes existed keep full access.
+ALTER TABLE api_keys ADD COLUMN scopes VARCHAR(64)[] NOT NULL DEFAULT ARRAY[
+    'businesses:read', 'businesses:write',
+    'accounts:read', 'accounts:write',
+    'transactions:read', 'transactions:write',
+    'fees:read', 'fees:write',
+    'webhooks:read', 'webhooks:manage',
+    'integrity:read',
+    'api_keys:manage'
+];
+
+ALTER TABLE api_keys ALTER COLUMN scopes DROP DEFAULT;
@@ -0,0 +1,14 @@
+-- Secret for HMAC-signed requests. Keys issued before request signing have
+-- none and get one when rotated.
+ALTER TABLE api_keys ADD COLUMN signing_secret TEXT;
+
+-- Nonces of signed requests, kept until their timestamps fall outside the
+-- allowed skew so that a captured request cannot be replayed.
+CREATE TABLE request_nonces (
+    api_key_id UUID NOT NULL REFERENCES api_keys(id),
+    nonce VARCHAR(128) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (api_key_id, nonce)
+);
+
+CREATE INDEX idx_request_nonces_created ON request_nonces(created_at);
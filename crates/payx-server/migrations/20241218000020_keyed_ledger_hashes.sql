@@ -0,0 +1,9 @@
+-- Ledger hashes become HMACs keyed with LEDGER_HASH_KEY, which the database
+-- never sees. Chains can no longer be rebuilt in SQL, as earlier migrations
+-- did; existing chains keep their unkeyed hashes until
+-- `payx-server rekey-ledger` verifies and rewrites them.
+--
+-- ledger_head is a keyed hash of the account's latest entry hash, written
+-- with every entry, so that entries deleted from the end of a chain are
+-- detected. It stays NULL for chains that have not been rekeyed yet.
+ALTER TABLE accounts ADD COLUMN ledger_head VARCHAR(64);
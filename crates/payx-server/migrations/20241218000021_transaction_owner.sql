@@ -0,0 +1,23 @@
+-- Transactions get an owning business, so that idempotency keys are unique
+-- per business rather than across all of them, and so that lookups no longer
+-- go through the transaction_businesses view. Existing transactions belong
+-- to the business of the accounts they touch.
+ALTER TABLE transactions ADD COLUMN business_id UUID REFERENCES businesses(id);
+
+UPDATE transactions t
+SET business_id = COALESCE(
+    (SELECT business_id FROM accounts WHERE id = t.source_account_id),
+    (SELECT business_id FROM accounts WHERE id = t.destination_account_id),
+    (SELECT a.business_id FROM ledger_entries le
+     JOIN accounts a ON a.id = le.account_id
+     WHERE le.transaction_id = t.id
+     LIMIT 1)
+);
+
+ALTER TABLE transactions ALTER COLUMN business_id SET NOT NULL;
+
+DROP INDEX idx_transactions_idempotency;
+CREATE UNIQUE INDEX idx_transactions_idempotency ON transactions(business_id, idempotency_key) WHERE idempotency_key IS NOT NULL;
+CREATE INDEX idx_transactions_business ON transactions(business_id, created_at DESC);
+
+DROP VIEW transaction_businesses;
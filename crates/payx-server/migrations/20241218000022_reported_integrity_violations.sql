@@ -0,0 +1,10 @@
+-- Violations the integrity checker has sent an integrity.violation webhook
+-- for, so that later runs only notify about new ones. A row is removed once
+-- a run no longer finds its violation, so a recurrence is reported again.
+CREATE TABLE reported_integrity_violations (
+    business_id UUID NOT NULL REFERENCES businesses(id),
+    kind VARCHAR(32) NOT NULL,
+    subject_id UUID NOT NULL,
+    reported_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (business_id, kind, subject_id)
+);
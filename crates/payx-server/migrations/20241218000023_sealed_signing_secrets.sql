@@ -0,0 +1,7 @@
+-- Signing secrets are encrypted with AES-256-GCM under SECRET_ENCRYPTION_KEY
+-- and stored as 'v1:' followed by the URL-safe base64 of the nonce and
+-- ciphertext. The server needs them back to verify signatures, so they cannot
+-- be hashed like the keys themselves. Secrets stored in plaintext before this
+-- migration are encrypted when the server starts.
+COMMENT ON COLUMN api_keys.signing_secret IS
+    'AES-256-GCM sealed under SECRET_ENCRYPTION_KEY, bound to the key id';
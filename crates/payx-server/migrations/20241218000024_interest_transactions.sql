@@ -0,0 +1,6 @@
+-- Interest used to be posted as `credit` transactions from the expense
+-- account. Give the ones already posted their own type, so that they read
+-- correctly and stay out of velocity limit windows like new ones.
+UPDATE transactions SET type = 'interest'
+WHERE type = 'credit'
+    AND id IN (SELECT transaction_id FROM interest_accruals WHERE transaction_id IS NOT NULL);
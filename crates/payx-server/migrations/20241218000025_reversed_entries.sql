@@ -0,0 +1,40 @@
+-- Link each reversal leg to the original leg it gives back, so partial
+-- refunds can track how much of every leg (fees included) has already been
+-- refunded instead of rounding each refund independently.
+ALTER TABLE ledger_entries ADD COLUMN reversed_entry_id UUID REFERENCES ledger_entries(id);
+
+CREATE INDEX idx_ledger_entries_reversed_entry ON ledger_entries(reversed_entry_id)
+WHERE reversed_entry_id IS NOT NULL;
+
+-- Existing reversal legs were posted pro rata, which keeps the order of
+-- amounts within an account and side, so pair them up by that order
+WITH reversal_legs AS (
+    SELECT le.id, le.account_id, le.entry_type, t.reversed_transaction_id AS original_id,
+        ROW_NUMBER() OVER (
+            PARTITION BY le.transaction_id, le.account_id, le.entry_type
+            ORDER BY le.amount, le.seq
+        ) AS n
+    FROM ledger_entries le
+    JOIN transactions t ON t.id = le.transaction_id
+    WHERE t.reversed_transaction_id IS NOT NULL
+),
+original_legs AS (
+    SELECT le.id, le.transaction_id, le.account_id, le.entry_type,
+        ROW_NUMBER() OVER (
+            PARTITION BY le.transaction_id, le.account_id, le.entry_type
+            ORDER BY le.amount, le.seq
+        ) AS n
+    FROM ledger_entries le
+    WHERE le.transaction_id IN (
+        SELECT reversed_transaction_id FROM transactions WHERE reversed_transaction_id IS NOT NULL
+    )
+)
+UPDATE ledger_entries e
+SET reversed_entry_id = o.id
+FROM reversal_legs r
+JOIN original_legs o
+    ON o.transaction_id = r.original_id
+    AND o.account_id = r.account_id
+    AND o.entry_type <> r.entry_type
+    AND o.n = r.n
+WHERE e.id = r.id;
@@ -1,17 +1,27 @@
 use std::sync::Arc;
 
-use axum::extract::{Path, Query, State};
-use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
-use chrono::Utc;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
 use serde::Deserialize;
+use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::api::handlers::transactions::enqueue_webhook;
+use crate::api::middleware::auth::AuthContext;
 use crate::domain::{
-    Account, AccountResponse, CreateAccountRequest, Transaction, TransactionResponse,
+    Account, AccountLimits, AccountResponse, AccountRollupResponse, AccountStatement,
+    AccountStatus, BalanceResponse, BalanceSnapshot, ChainVerification, CreateAccountRequest,
+    EntryType, LedgerEntry, LedgerEntryResponse, RollupBalance, SetAccountLimitsRequest,
+    StatementFormat, StatementLine, Transaction, TransactionResponse, TransactionStatus,
+    TransactionType, UpdateAccountRequest,
 };
 use crate::error::{AppError, Result};
+use crate::ledger;
 use crate::AppState;
 
 #[derive(Deserialize)]
@@ -19,7 +29,11 @@ pub struct ListQuery {
     #[serde(default = "default_limit")]
     limit: i64,
     offset: Option<i64>,
-    business_id: Option<Uuid>,
+    external_id: Option<String>,
+    parent_account_id: Option<Uuid>,
+    /// Settlement accounts are internal, so they are only listed on request.
+    #[serde(default)]
+    include_settlement: bool,
 }
 
 fn default_limit() -> i64 {
@@ -28,29 +42,28 @@ fn default_limit() -> i64 {
 
 pub async fn list(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Query(query): Query<ListQuery>,
 ) -> Result<Json<Vec<AccountResponse>>> {
-    let accounts: Vec<Account> = match query.business_id {
-        Some(business_id) => {
-            sqlx::query_as(
-                "SELECT * FROM accounts WHERE business_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
-            )
-            .bind(business_id)
-            .bind(query.limit)
-            .bind(query.offset.unwrap_or(0))
-            .fetch_all(&state.db)
-            .await?
-        }
-        None => {
-            sqlx::query_as(
-                "SELECT * FROM accounts ORDER BY created_at DESC LIMIT $1 OFFSET $2",
-            )
-            .bind(query.limit)
-            .bind(query.offset.unwrap_or(0))
-            .fetch_all(&state.db)
-            .await?
-        }
-    };
+    let accounts: Vec<Account> = sqlx::query_as(
+        r#"
+        SELECT * FROM accounts
+        WHERE business_id = $1
+        AND ($2::varchar IS NULL OR external_id = $2)
+        AND ($3::uuid IS NULL OR parent_account_id = $3)
+        AND ($6 OR NOT is_settlement)
+        ORDER BY created_at DESC
+        LIMIT $4 OFFSET $5
+        "#,
+    )
+    .bind(auth.api_key.business_id)
+    .bind(&query.external_id)
+    .bind(query.parent_account_id)
+    .bind(query.limit)
+    .bind(query.offset.unwrap_or(0))
+    .bind(query.include_settlement)
+    .fetch_all(&state.db)
+    .await?;
 
     Ok(Json(
         accounts.into_iter().map(AccountResponse::from).collect(),
@@ -59,43 +72,368 @@ pub async fn list(
 
 pub async fn create(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Json(req): Json<CreateAccountRequest>,
 ) -> Result<impl IntoResponse> {
+    let business_id = auth.api_key.business_id;
+    if let Some(requested) = req.business_id.filter(|id| *id != business_id) {
+        return Err(AppError::BusinessNotFound(requested));
+    }
+
+    if req.overdraft_limit < Decimal::ZERO {
+        return Err(AppError::Validation(
+            "overdraft_limit must not be negative".into(),
+        ));
+    }
+
+    if let Some(parent_id) = req.parent_account_id {
+        let parent = find_account(&state.db, business_id, parent_id)
+            .await
+            .map_err(|e| match e {
+                AppError::AccountNotFound(_) => {
+                    AppError::Validation(format!("parent account not found: {}", parent_id))
+                }
+                e => e,
+            })?;
+
+        if parent.is_settlement || parent.status == AccountStatus::Closed {
+            return Err(AppError::Validation(
+                "parent account cannot have sub-accounts".into(),
+            ));
+        }
+    }
+
     let id = Uuid::new_v4();
     let now = Utc::now();
+    let mut tx = state.db.begin().await?;
 
     let account: Account = sqlx::query_as(
         r#"
-        INSERT INTO accounts (id, business_id, account_type, currency, balance, available_balance, version, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $5, 0, $6, $6)
+        INSERT INTO accounts (id, business_id, account_type, currency, balance, available_balance, overdraft_limit, external_id, name, metadata, parent_account_id, version, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $5, $6, $7, $8, $9, $10, 0, $11, $11)
         RETURNING *
         "#,
     )
     .bind(id)
-    .bind(req.business_id)
-    .bind(&req.account_type)
+    .bind(business_id)
+    .bind(req.account_type)
     .bind(&req.currency)
     .bind(req.initial_balance)
+    .bind(req.overdraft_limit)
+    .bind(&req.external_id)
+    .bind(&req.name)
+    .bind(&req.metadata)
+    .bind(req.parent_account_id)
     .bind(now)
-    .fetch_one(&state.db)
-    .await?;
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| external_id_conflict(e, req.external_id.as_deref()))?;
+
+    if !account.balance.is_zero() {
+        post_opening_balance(&mut tx, &account, now).await?;
+    }
+
+    tx.commit().await?;
 
     Ok((StatusCode::CREATED, Json(AccountResponse::from(account))))
 }
 
+/// Posts the new account's balance as an `opening` transaction against the
+/// business's settlement account, so the ledger accounts for all of it.
+async fn post_opening_balance(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    account: &Account,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let settlement_id =
+        ledger::settlement_account_id(tx, account.business_id, &account.currency).await?;
+    let settlement = ledger::lock_accounts(tx, &[settlement_id])
+        .await?
+        .remove(&settlement_id)
+        .ok_or(AppError::AccountNotFound(settlement_id))?;
+
+    let normal = account.account_type.normal_balance();
+    let entry_type = if account.balance > Decimal::ZERO {
+        normal
+    } else {
+        normal.opposite()
+    };
+    let amount = account.balance.abs();
+    let txn_id = Uuid::new_v4();
+
+    let transaction: Transaction = sqlx::query_as(
+        r#"
+        INSERT INTO transactions (id, business_id, type, status, destination_account_id, amount, currency, description, created_at, completed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(txn_id)
+    .bind(account.business_id)
+    .bind(TransactionType::Opening)
+    .bind(TransactionStatus::Completed)
+    .bind(account.id)
+    .bind(amount)
+    .bind(&account.currency)
+    .bind("Opening balance")
+    .bind(now)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    ledger::post_entry(
+        tx,
+        txn_id,
+        account.id,
+        entry_type,
+        amount,
+        account.balance,
+        now,
+    )
+    .await?;
+    ledger::post_settlement(tx, &settlement, txn_id, entry_type.opposite(), amount, now).await?;
+
+    enqueue_webhook(
+        tx,
+        account.business_id,
+        "transaction.completed",
+        &transaction,
+    )
+    .await?;
+
+    Ok(())
+}
+
 pub async fn get(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<AccountResponse>> {
-    let account: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1")
+    let account = find_account(&state.db, auth.api_key.business_id, id).await?;
+
+    Ok(Json(AccountResponse::from(account)))
+}
+
+/// Fetches one of the business's accounts. Another business's account is
+/// reported as not found.
+pub async fn find_account(db: &PgPool, business_id: Uuid, id: Uuid) -> Result<Account> {
+    sqlx::query_as("SELECT * FROM accounts WHERE id = $1 AND business_id = $2")
         .bind(id)
-        .fetch_optional(&state.db)
+        .bind(business_id)
+        .fetch_optional(db)
         .await?
-        .ok_or(AppError::AccountNotFound(id))?;
+        .ok_or(AppError::AccountNotFound(id))
+}
+
+pub async fn update(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateAccountRequest>,
+) -> Result<Json<AccountResponse>> {
+    let mut tx = state.db.begin().await?;
+
+    let account: Account =
+        sqlx::query_as("SELECT * FROM accounts WHERE id = $1 AND business_id = $2 FOR UPDATE")
+            .bind(id)
+            .bind(auth.api_key.business_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::AccountNotFound(id))?;
+
+    if account.status == AccountStatus::Closed {
+        return Err(AppError::AccountNotActive {
+            account_id: id,
+            status: account.status.as_str().into(),
+        });
+    }
+
+    let overdraft_limit = req.overdraft_limit.unwrap_or(account.overdraft_limit);
+    if overdraft_limit < Decimal::ZERO {
+        return Err(AppError::Validation(
+            "overdraft_limit must not be negative".into(),
+        ));
+    }
 
+    // The limit cannot be lowered below what the account already uses
+    let used = -account.balance.min(account.available_balance);
+    if overdraft_limit < used {
+        return Err(AppError::Validation(format!(
+            "overdraft_limit must be at least the current overdraft {}",
+            used
+        )));
+    }
+
+    // The row is locked, so omitted fields can be filled in from it
+    let external_id = req.external_id.unwrap_or(account.external_id);
+    let name = req.name.unwrap_or(account.name);
+    let metadata = req.metadata.unwrap_or(account.metadata);
+
+    let account: Account = sqlx::query_as(
+        r#"
+        UPDATE accounts
+        SET overdraft_limit = $1,
+            external_id = $2,
+            name = $3,
+            metadata = $4,
+            version = version + 1,
+            updated_at = $5
+        WHERE id = $6
+        RETURNING *
+        "#,
+    )
+    .bind(overdraft_limit)
+    .bind(&external_id)
+    .bind(&name)
+    .bind(&metadata)
+    .bind(Utc::now())
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| external_id_conflict(e, external_id.as_deref()))?;
+
+    tx.commit().await?;
+    Ok(Json(AccountResponse::from(account)))
+}
+
+/// Maps a violation of the per-business external ID index to a conflict.
+fn external_id_conflict(e: sqlx::Error, external_id: Option<&str>) -> AppError {
+    match (&e, external_id) {
+        (sqlx::Error::Database(db), Some(external_id))
+            if db.constraint() == Some("idx_accounts_external_id") =>
+        {
+            AppError::DuplicateExternalId(external_id.to_string())
+        }
+        _ => AppError::Database(e),
+    }
+}
+
+pub async fn freeze(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AccountResponse>> {
+    let account = change_status(&state, &auth, id, AccountStatus::Frozen).await?;
+    Ok(Json(AccountResponse::from(account)))
+}
+
+pub async fn unfreeze(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AccountResponse>> {
+    let account = change_status(&state, &auth, id, AccountStatus::Active).await?;
+    Ok(Json(AccountResponse::from(account)))
+}
+
+pub async fn close(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AccountResponse>> {
+    let account = change_status(&state, &auth, id, AccountStatus::Closed).await?;
     Ok(Json(AccountResponse::from(account)))
 }
 
+async fn change_status(
+    state: &AppState,
+    auth: &AuthContext,
+    id: Uuid,
+    status: AccountStatus,
+) -> Result<Account> {
+    let mut tx = state.db.begin().await?;
+    let now = Utc::now();
+
+    let account: Account =
+        sqlx::query_as("SELECT * FROM accounts WHERE id = $1 AND business_id = $2 FOR UPDATE")
+            .bind(id)
+            .bind(auth.api_key.business_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::AccountNotFound(id))?;
+
+    if account.is_settlement {
+        return Err(AppError::Validation(
+            "settlement accounts cannot change status".into(),
+        ));
+    }
+
+    let event_type = match (account.status, status) {
+        (AccountStatus::Active, AccountStatus::Frozen) => "account.frozen",
+        (AccountStatus::Frozen, AccountStatus::Active) => "account.unfrozen",
+        (AccountStatus::Active | AccountStatus::Frozen, AccountStatus::Closed) => "account.closed",
+        (from, to) => {
+            return Err(AppError::InvalidAccountTransition {
+                account_id: id,
+                from: from.as_str().into(),
+                to: to.as_str().into(),
+            })
+        }
+    };
+
+    if status == AccountStatus::Closed {
+        if !account.balance.is_zero() {
+            return Err(AppError::Validation(format!(
+                "account balance must be zero to close, is {}",
+                account.balance
+            )));
+        }
+
+        let has_active_holds: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM holds WHERE account_id = $1 AND status = 'active')",
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if has_active_holds {
+            return Err(AppError::Validation(
+                "account has active holds; capture or void them before closing".into(),
+            ));
+        }
+
+        let has_open_children: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM accounts WHERE parent_account_id = $1 AND status <> 'closed')",
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if has_open_children {
+            return Err(AppError::Validation(
+                "account has open sub-accounts; close them first".into(),
+            ));
+        }
+    }
+
+    let account: Account = sqlx::query_as(
+        r#"
+        UPDATE accounts
+        SET status = $1,
+            closed_at = CASE WHEN $1 = 'closed' THEN $2 ELSE closed_at END,
+            version = version + 1,
+            updated_at = $2
+        WHERE id = $3
+        RETURNING *
+        "#,
+    )
+    .bind(status)
+    .bind(now)
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    enqueue_webhook(
+        &mut tx,
+        account.business_id,
+        event_type,
+        &AccountResponse::from(account.clone()),
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(account)
+}
+
 #[derive(Deserialize)]
 pub struct ListTransactionsQuery {
     #[serde(default = "default_limit")]
@@ -105,21 +443,19 @@ pub struct ListTransactionsQuery {
 
 pub async fn list_transactions(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Path(account_id): Path<Uuid>,
     Query(query): Query<ListTransactionsQuery>,
 ) -> Result<Json<Vec<TransactionResponse>>> {
-    let _account: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1")
-        .bind(account_id)
-        .fetch_optional(&state.db)
-        .await?
-        .ok_or(AppError::AccountNotFound(account_id))?;
+    find_account(&state.db, auth.api_key.business_id, account_id).await?;
 
     let transactions: Vec<Transaction> = match query.cursor {
         Some(cursor) => {
             sqlx::query_as(
                 r#"
                 SELECT * FROM transactions
-                WHERE (source_account_id = $1 OR destination_account_id = $1)
+                WHERE (source_account_id = $1 OR destination_account_id = $1
+                    OR id IN (SELECT transaction_id FROM ledger_entries WHERE account_id = $1))
                 AND id < $2
                 ORDER BY created_at DESC
                 LIMIT $3
@@ -136,6 +472,7 @@ pub async fn list_transactions(
                 r#"
                 SELECT * FROM transactions
                 WHERE source_account_id = $1 OR destination_account_id = $1
+                OR id IN (SELECT transaction_id FROM ledger_entries WHERE account_id = $1)
                 ORDER BY created_at DESC
                 LIMIT $2
                 "#,
@@ -154,3 +491,359 @@ pub async fn list_transactions(
             .collect(),
     ))
 }
+
+#[derive(Deserialize)]
+pub struct ListLedgerQuery {
+    #[serde(default = "default_limit")]
+    limit: i64,
+    cursor: Option<Uuid>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+pub async fn list_ledger_entries(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(account_id): Path<Uuid>,
+    Query(query): Query<ListLedgerQuery>,
+) -> Result<Json<Vec<LedgerEntryResponse>>> {
+    find_account(&state.db, auth.api_key.business_id, account_id).await?;
+
+    let entries: Vec<LedgerEntry> = sqlx::query_as(
+        r#"
+        SELECT * FROM ledger_entries
+        WHERE account_id = $1
+        AND ($2::timestamptz IS NULL OR created_at >= $2)
+        AND ($3::timestamptz IS NULL OR created_at < $3)
+        AND ($4::uuid IS NULL OR seq < (SELECT seq FROM ledger_entries WHERE id = $4))
+        ORDER BY seq DESC
+        LIMIT $5
+        "#,
+    )
+    .bind(account_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.cursor)
+    .bind(query.limit)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(
+        entries.into_iter().map(LedgerEntryResponse::from).collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct BalanceQuery {
+    as_of: Option<DateTime<Utc>>,
+}
+
+pub async fn get_balance(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(account_id): Path<Uuid>,
+    Query(query): Query<BalanceQuery>,
+) -> Result<Json<BalanceResponse>> {
+    let account = find_account(&state.db, auth.api_key.business_id, account_id).await?;
+
+    let as_of = query.as_of.unwrap_or_else(Utc::now);
+    if as_of < account.created_at {
+        return Err(AppError::Validation(format!(
+            "account did not exist at {}",
+            as_of
+        )));
+    }
+
+    let balance = ledger::balance_at(&state.db, &account, as_of).await?;
+
+    // Funds reserved by holds that were still open at that moment
+    let held: Decimal = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(amount), 0) FROM holds
+        WHERE account_id = $1
+        AND created_at <= $2
+        AND (released_at IS NULL OR released_at > $2)
+        "#,
+    )
+    .bind(account_id)
+    .bind(as_of)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(BalanceResponse {
+        account_id,
+        currency: account.currency,
+        balance,
+        available_balance: balance - held,
+        as_of,
+    }))
+}
+
+pub async fn verify_ledger(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ChainVerification>> {
+    find_account(&state.db, auth.api_key.business_id, id).await?;
+    let verification = ledger::verify_chain(&state.db, id).await?;
+    Ok(Json(verification))
+}
+
+pub async fn get_rollup(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AccountRollupResponse>> {
+    let account = find_account(&state.db, auth.api_key.business_id, id).await?;
+    let normal_balance = account.account_type.normal_balance();
+
+    // Balances are stored with each account's own normal side positive, so a
+    // descendant on the other side counts against the total
+    let balances: Vec<RollupBalance> = sqlx::query_as(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT * FROM accounts WHERE id = $1
+            UNION ALL
+            SELECT a.* FROM accounts a
+            JOIN subtree s ON a.parent_account_id = s.id
+        )
+        SELECT currency,
+            SUM(CASE WHEN normal_balance = $2 THEN balance ELSE -balance END) AS balance,
+            SUM(CASE WHEN normal_balance = $2 THEN available_balance ELSE -available_balance END)
+                AS available_balance,
+            COUNT(*) AS accounts
+        FROM subtree
+        GROUP BY currency
+        ORDER BY currency
+        "#,
+    )
+    .bind(id)
+    .bind(normal_balance)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(AccountRollupResponse {
+        account_id: id,
+        normal_balance,
+        balances,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct DailyBalancesQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+/// Closing balances of ended days, oldest first. Days are calendar days in
+/// the business's timezone; `from` and `to` are inclusive.
+pub async fn get_daily_balances(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DailyBalancesQuery>,
+) -> Result<Json<Vec<BalanceSnapshot>>> {
+    find_account(&state.db, auth.api_key.business_id, id).await?;
+
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return Err(AppError::Validation("from must not be after to".into()));
+        }
+    }
+
+    let snapshots: Vec<BalanceSnapshot> = sqlx::query_as(
+        r#"
+        SELECT * FROM balance_snapshots
+        WHERE account_id = $1
+        AND ($2::date IS NULL OR snapshot_date >= $2)
+        AND ($3::date IS NULL OR snapshot_date <= $3)
+        ORDER BY snapshot_date
+        "#,
+    )
+    .bind(id)
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(snapshots))
+}
+
+/// Longest period a single statement covers.
+const MAX_STATEMENT_DAYS: i64 = 366;
+
+#[derive(Deserialize)]
+pub struct StatementQuery {
+    from: NaiveDate,
+    to: NaiveDate,
+    #[serde(default)]
+    format: StatementFormat,
+}
+
+/// The account's ledger lines from the start of `from` to the end of `to`,
+/// in the business's timezone, between its opening and closing balances.
+pub async fn get_statement(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<StatementQuery>,
+) -> Result<Response> {
+    let account = find_account(&state.db, auth.api_key.business_id, id).await?;
+
+    if query.from > query.to {
+        return Err(AppError::Validation("from must not be after to".into()));
+    }
+    if (query.to - query.from).num_days() >= MAX_STATEMENT_DAYS {
+        return Err(AppError::Validation(format!(
+            "statements cover at most {} days",
+            MAX_STATEMENT_DAYS
+        )));
+    }
+
+    let timezone: String = sqlx::query_scalar("SELECT timezone FROM businesses WHERE id = $1")
+        .bind(account.business_id)
+        .fetch_one(&state.db)
+        .await?;
+    let tz = timezone.parse::<Tz>().unwrap_or(Tz::UTC);
+    let start = ledger::day_start(tz, query.from);
+    let end = ledger::day_start(tz, query.to + Days::new(1));
+
+    let opening_balance = ledger::balance_at(&state.db, &account, start).await?;
+
+    // Bounds match balance_at, which counts an entry at `start` as before it
+    let lines: Vec<StatementLine> = sqlx::query_as(
+        r#"
+        SELECT le.id AS entry_id, le.transaction_id, t.type AS transaction_type, t.description,
+            le.entry_type, le.amount, le.balance_after AS balance, le.created_at
+        FROM ledger_entries le
+        JOIN transactions t ON t.id = le.transaction_id
+        WHERE le.account_id = $1 AND le.created_at > $2 AND le.created_at <= $3
+        ORDER BY le.seq
+        "#,
+    )
+    .bind(id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(&state.db)
+    .await?;
+
+    let total = |entry_type| {
+        lines
+            .iter()
+            .filter(|l| l.entry_type == entry_type)
+            .map(|l| l.amount)
+            .sum::<Decimal>()
+    };
+
+    let statement = AccountStatement {
+        account_id: id,
+        currency: account.currency,
+        timezone,
+        from: query.from,
+        to: query.to,
+        opening_balance,
+        total_credits: total(EntryType::Credit),
+        total_debits: total(EntryType::Debit),
+        closing_balance: lines.last().map_or(opening_balance, |l| l.balance),
+        lines,
+    };
+
+    Ok(match query.format {
+        StatementFormat::Json => Json(statement).into_response(),
+        StatementFormat::Csv => {
+            let filename = format!("statement-{}-{}-{}.csv", id, query.from, query.to);
+            (
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{}\"", filename),
+                    ),
+                ],
+                statement.to_csv(),
+            )
+                .into_response()
+        }
+    })
+}
+
+pub async fn get_limits(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AccountLimits>> {
+    find_account(&state.db, auth.api_key.business_id, id).await?;
+
+    let limits: Option<AccountLimits> =
+        sqlx::query_as("SELECT * FROM account_limits WHERE account_id = $1")
+            .bind(id)
+            .fetch_optional(&state.db)
+            .await?;
+
+    Ok(Json(limits.unwrap_or(AccountLimits {
+        account_id: id,
+        ..Default::default()
+    })))
+}
+
+pub async fn set_limits(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetAccountLimitsRequest>,
+) -> Result<Json<AccountLimits>> {
+    if req
+        .max_transaction_amount
+        .is_some_and(|max| max <= Decimal::ZERO)
+    {
+        return Err(AppError::Validation(
+            "max_transaction_amount must be positive".into(),
+        ));
+    }
+
+    let negative_amount = [req.daily_debit_amount, req.monthly_debit_amount]
+        .into_iter()
+        .flatten()
+        .any(|max| max < Decimal::ZERO);
+    let negative_count = [req.daily_debit_count, req.monthly_debit_count]
+        .into_iter()
+        .flatten()
+        .any(|max| max < 0);
+    if negative_amount || negative_count {
+        return Err(AppError::Validation("limits must not be negative".into()));
+    }
+
+    let account = find_account(&state.db, auth.api_key.business_id, id).await?;
+
+    if account.is_settlement {
+        return Err(AppError::Validation(
+            "settlement accounts cannot have limits".into(),
+        ));
+    }
+
+    let limits: AccountLimits = sqlx::query_as(
+        r#"
+        INSERT INTO account_limits (account_id, max_transaction_amount, daily_debit_count, daily_debit_amount, monthly_debit_count, monthly_debit_amount, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (account_id) DO UPDATE SET
+            max_transaction_amount = EXCLUDED.max_transaction_amount,
+            daily_debit_count = EXCLUDED.daily_debit_count,
+            daily_debit_amount = EXCLUDED.daily_debit_amount,
+            monthly_debit_count = EXCLUDED.monthly_debit_count,
+            monthly_debit_amount = EXCLUDED.monthly_debit_amount,
+            updated_at = EXCLUDED.updated_at
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(req.max_transaction_amount)
+    .bind(req.daily_debit_count)
+    .bind(req.daily_debit_amount)
+    .bind(req.monthly_debit_count)
+    .bind(req.monthly_debit_amount)
+    .bind(Utc::now())
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(limits))
+}
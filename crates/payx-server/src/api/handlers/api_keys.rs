@@ -0,0 +1,300 @@
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::api::middleware::auth::AuthContext;
+use crate::domain::{
+    seal_signing_secret, ApiKey, ApiKeySummary, CreateApiKeyRequest, GeneratedApiKey,
+    RotateApiKeyRequest, Scope, SEALED_PREFIX,
+};
+use crate::error::{AppError, Result};
+use crate::workers::api_key_sync::notify_api_key_changed;
+use crate::AppState;
+
+const DEFAULT_ROTATION_OVERLAP_SECONDS: i64 = 24 * 60 * 60;
+const MAX_ROTATION_OVERLAP_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    #[serde(default = "default_limit")]
+    limit: i64,
+    offset: Option<i64>,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+/// Lists the business's keys that have not been revoked, including expired
+/// ones.
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<Vec<ApiKeySummary>>> {
+    let keys: Vec<ApiKey> = sqlx::query_as(
+        r#"
+        SELECT * FROM api_keys
+        WHERE business_id = $1 AND revoked_at IS NULL
+        ORDER BY created_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(auth.api_key.business_id)
+    .bind(query.limit)
+    .bind(query.offset.unwrap_or(0))
+    .fetch_all(&state.db)
+    .await?;
+
+    // Include uses this instance has not written back yet.
+    let summaries = keys
+        .into_iter()
+        .map(|key| {
+            let pending = state.api_keys.pending_last_used(key.id);
+            let mut summary = ApiKeySummary::from(key);
+            summary.last_used_at = summary.last_used_at.max(pending);
+            summary
+        })
+        .collect();
+
+    Ok(Json(summaries))
+}
+
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse> {
+    validate_expires_at(req.expires_at)?;
+
+    let mut scopes = req.scopes.unwrap_or_else(|| auth.api_key.scopes.clone());
+    scopes.sort();
+    scopes.dedup();
+    if scopes.is_empty() {
+        return Err(AppError::Validation("scopes must not be empty".into()));
+    }
+    ensure_grantable(&auth, &scopes)?;
+
+    let (api_key, generated) = ApiKey::generate(
+        auth.api_key.business_id,
+        req.name,
+        scopes,
+        req.expires_at,
+        &state.config.secret_encryption_key,
+    );
+
+    let mut tx = state.db.begin().await?;
+    insert_api_key(&mut tx, &api_key).await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(generated)))
+}
+
+/// Issues a replacement for a key with the same name and scopes. The old key
+/// keeps working until the overlap window ends, so callers can switch over
+/// without downtime.
+pub async fn rotate(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+    body: Bytes,
+) -> Result<impl IntoResponse> {
+    // An empty body rotates with the default overlap.
+    let req: RotateApiKeyRequest = if body.is_empty() {
+        RotateApiKeyRequest::default()
+    } else {
+        serde_json::from_slice(&body)
+            .map_err(|e| AppError::Validation(format!("invalid rotate request: {}", e)))?
+    };
+
+    let overlap_seconds = req
+        .overlap_seconds
+        .unwrap_or(DEFAULT_ROTATION_OVERLAP_SECONDS);
+    if !(0..=MAX_ROTATION_OVERLAP_SECONDS).contains(&overlap_seconds) {
+        return Err(AppError::Validation(format!(
+            "overlap_seconds must be between 0 and {}",
+            MAX_ROTATION_OVERLAP_SECONDS
+        )));
+    }
+    validate_expires_at(req.expires_at)?;
+
+    let mut tx = state.db.begin().await?;
+
+    let old: ApiKey = sqlx::query_as(
+        r#"
+        SELECT * FROM api_keys
+        WHERE id = $1 AND business_id = $2 AND revoked_at IS NULL
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .bind(auth.api_key.business_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| api_key_not_found(id))?;
+
+    if !old.is_valid() {
+        return Err(AppError::Validation(format!("api key has expired: {}", id)));
+    }
+    ensure_grantable(&auth, &old.scopes)?;
+
+    // Never extend the old key's life past an expiry it already had.
+    let retire_at = Utc::now() + Duration::seconds(overlap_seconds);
+    let previous_key_expires_at = old.expires_at.map_or(retire_at, |e| e.min(retire_at));
+
+    sqlx::query("UPDATE api_keys SET expires_at = $1 WHERE id = $2")
+        .bind(previous_key_expires_at)
+        .bind(old.id)
+        .execute(&mut *tx)
+        .await?;
+    notify_api_key_changed(&mut tx, old.id).await?;
+
+    let (mut api_key, generated) = ApiKey::generate(
+        old.business_id,
+        old.name.clone(),
+        old.scopes.clone(),
+        req.expires_at,
+        &state.config.secret_encryption_key,
+    );
+    api_key.rate_limit_per_minute = old.rate_limit_per_minute;
+    insert_api_key(&mut tx, &api_key).await?;
+
+    tx.commit().await?;
+    state.api_keys.invalidate(old.id);
+
+    #[derive(Serialize)]
+    struct Response {
+        api_key: GeneratedApiKey,
+        previous_key_expires_at: DateTime<Utc>,
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(Response {
+            api_key: generated,
+            previous_key_expires_at,
+        }),
+    ))
+}
+
+/// Revokes a key immediately. The business's last active key cannot be
+/// revoked, since nothing else could authenticate for it afterwards.
+pub async fn revoke(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let business_id = auth.api_key.business_id;
+    let mut tx = state.db.begin().await?;
+
+    let keys: Vec<ApiKey> = sqlx::query_as(
+        "SELECT * FROM api_keys WHERE business_id = $1 AND revoked_at IS NULL FOR UPDATE",
+    )
+    .bind(business_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let target = keys
+        .iter()
+        .find(|k| k.id == id)
+        .ok_or_else(|| api_key_not_found(id))?;
+    // A narrow key must not be able to lock out a wider one
+    ensure_grantable(&auth, &target.scopes)?;
+    if !keys.iter().any(|k| k.id != id && k.is_valid()) {
+        return Err(AppError::Validation(
+            "cannot revoke the last active api key".into(),
+        ));
+    }
+
+    sqlx::query("UPDATE api_keys SET revoked_at = $1 WHERE id = $2")
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    notify_api_key_changed(&mut tx, id).await?;
+
+    tx.commit().await?;
+    state.api_keys.invalidate(id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) async fn insert_api_key(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    api_key: &ApiKey,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO api_keys (id, business_id, key_hash, key_prefix, name, scopes, rate_limit_per_minute, created_at, expires_at, signing_secret)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#,
+    )
+    .bind(api_key.id)
+    .bind(api_key.business_id)
+    .bind(&api_key.key_hash)
+    .bind(&api_key.key_prefix)
+    .bind(&api_key.name)
+    .bind(&api_key.scopes)
+    .bind(api_key.rate_limit_per_minute)
+    .bind(api_key.created_at)
+    .bind(api_key.expires_at)
+    .bind(&api_key.signing_secret)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Encrypts signing secrets that were stored in plaintext, before they were
+/// sealed or by an instance that did not seal them yet. Runs at startup.
+pub(crate) async fn seal_signing_secrets(db: &PgPool, encryption_key: &[u8; 32]) -> Result<()> {
+    let plaintext: Vec<(Uuid, String)> = sqlx::query_as(
+        "SELECT id, signing_secret FROM api_keys WHERE signing_secret NOT LIKE $1 || '%'",
+    )
+    .bind(SEALED_PREFIX)
+    .fetch_all(db)
+    .await?;
+
+    for (id, secret) in plaintext {
+        sqlx::query(
+            "UPDATE api_keys SET signing_secret = $1 WHERE id = $2 AND signing_secret = $3",
+        )
+        .bind(seal_signing_secret(encryption_key, id, &secret))
+        .bind(id)
+        .bind(&secret)
+        .execute(db)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// A key can only hand out scopes it holds itself, so `api_keys:manage` does
+/// not turn a read-only key into one that can move money.
+fn ensure_grantable(auth: &AuthContext, scopes: &[Scope]) -> Result<()> {
+    match scopes.iter().find(|s| !auth.api_key.has_scope(**s)) {
+        Some(scope) => Err(AppError::InsufficientScope(*scope)),
+        None => Ok(()),
+    }
+}
+
+fn validate_expires_at(expires_at: Option<DateTime<Utc>>) -> Result<()> {
+    if expires_at.is_some_and(|e| e <= Utc::now()) {
+        return Err(AppError::Validation(
+            "expires_at must be in the future".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn api_key_not_found(id: Uuid) -> AppError {
+    AppError::NotFound(format!("api key not found: {}", id))
+}
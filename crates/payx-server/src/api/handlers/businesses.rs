@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use axum::extract::{Path, Query, State};
+use axum::extract::{Extension, Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::Json;
@@ -10,7 +10,11 @@ use rand::RngCore;
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::domain::{Business, CreateBusinessRequest, GeneratedApiKey, UpdateBusinessRequest};
+use crate::api::handlers::api_keys::insert_api_key;
+use crate::api::middleware::auth::AuthContext;
+use crate::domain::{
+    ApiKey, Business, CreateBusinessRequest, GeneratedApiKey, Scope, UpdateBusinessRequest,
+};
 use crate::error::{AppError, Result};
 use crate::AppState;
 
@@ -25,16 +29,20 @@ fn default_limit() -> i64 {
     50
 }
 
+/// Lists the businesses the API key can see, which is only its own.
 pub async fn list(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Query(query): Query<ListQuery>,
 ) -> Result<Json<Vec<Business>>> {
-    let businesses: Vec<Business> =
-        sqlx::query_as("SELECT * FROM businesses ORDER BY created_at DESC LIMIT $1 OFFSET $2")
-            .bind(query.limit)
-            .bind(query.offset.unwrap_or(0))
-            .fetch_all(&state.db)
-            .await?;
+    let businesses: Vec<Business> = sqlx::query_as(
+        "SELECT * FROM businesses WHERE id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+    )
+    .bind(auth.api_key.business_id)
+    .bind(query.limit)
+    .bind(query.offset.unwrap_or(0))
+    .fetch_all(&state.db)
+    .await?;
 
     Ok(Json(businesses))
 }
@@ -43,6 +51,10 @@ pub async fn create(
     State(state): State<Arc<AppState>>,
     Json(req): Json<CreateBusinessRequest>,
 ) -> Result<impl IntoResponse> {
+    if let Some(timezone) = &req.timezone {
+        validate_timezone(timezone)?;
+    }
+
     let id = Uuid::new_v4();
     let now = Utc::now();
 
@@ -50,10 +62,12 @@ pub async fn create(
     rand::thread_rng().fill_bytes(&mut secret_bytes);
     let webhook_secret = URL_SAFE_NO_PAD.encode(secret_bytes);
 
+    let mut tx = state.db.begin().await?;
+
     let business: Business = sqlx::query_as(
         r#"
-        INSERT INTO businesses (id, name, email, webhook_url, webhook_secret, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $6)
+        INSERT INTO businesses (id, name, email, webhook_url, webhook_secret, timezone, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
         RETURNING *
         "#,
     )
@@ -62,26 +76,21 @@ pub async fn create(
     .bind(&req.email)
     .bind(&req.webhook_url)
     .bind(&webhook_secret)
+    .bind(req.timezone.as_deref().unwrap_or("UTC"))
     .bind(now)
-    .fetch_one(&state.db)
+    .fetch_one(&mut *tx)
     .await?;
 
-    let (api_key, generated) = crate::domain::ApiKey::generate(business.id);
+    let (api_key, generated) = ApiKey::generate(
+        business.id,
+        None,
+        Scope::ALL.to_vec(),
+        None,
+        &state.config.secret_encryption_key,
+    );
+    insert_api_key(&mut tx, &api_key).await?;
 
-    sqlx::query(
-        r#"
-        INSERT INTO api_keys (id, business_id, key_hash, key_prefix, rate_limit_per_minute, created_at)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        "#,
-    )
-    .bind(api_key.id)
-    .bind(api_key.business_id)
-    .bind(&api_key.key_hash)
-    .bind(&api_key.key_prefix)
-    .bind(api_key.rate_limit_per_minute)
-    .bind(api_key.created_at)
-    .execute(&state.db)
-    .await?;
+    tx.commit().await?;
 
     #[derive(serde::Serialize)]
     struct Response {
@@ -102,8 +111,13 @@ pub async fn create(
 
 pub async fn get(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<Business>> {
+    if id != auth.api_key.business_id {
+        return Err(AppError::BusinessNotFound(id));
+    }
+
     let business: Business = sqlx::query_as("SELECT * FROM businesses WHERE id = $1")
         .bind(id)
         .fetch_optional(&state.db)
@@ -115,15 +129,25 @@ pub async fn get(
 
 pub async fn update(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Path(id): Path<Uuid>,
     Json(req): Json<UpdateBusinessRequest>,
 ) -> Result<Json<Business>> {
+    if id != auth.api_key.business_id {
+        return Err(AppError::BusinessNotFound(id));
+    }
+
+    if let Some(timezone) = &req.timezone {
+        validate_timezone(timezone)?;
+    }
+
     let business: Business = sqlx::query_as(
         r#"
         UPDATE businesses
         SET name = COALESCE($2, name),
             webhook_url = COALESCE($3, webhook_url),
-            updated_at = $4
+            timezone = COALESCE($4, timezone),
+            updated_at = $5
         WHERE id = $1
         RETURNING *
         "#,
@@ -131,6 +155,7 @@ pub async fn update(
     .bind(id)
     .bind(&req.name)
     .bind(&req.webhook_url)
+    .bind(&req.timezone)
     .bind(Utc::now())
     .fetch_optional(&state.db)
     .await?
@@ -138,3 +163,10 @@ pub async fn update(
 
     Ok(Json(business))
 }
+
+fn validate_timezone(timezone: &str) -> Result<()> {
+    timezone
+        .parse::<chrono_tz::Tz>()
+        .map(|_| ())
+        .map_err(|_| AppError::Validation(format!("unknown timezone: {}", timezone)))
+}
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::middleware::auth::AuthContext;
+use crate::domain::{
+    Account, AccountStatus, AccountType, CreateFeeRuleRequest, FeeRule, TransactionType,
+};
+use crate::error::{AppError, Result};
+use crate::AppState;
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    #[serde(default = "default_limit")]
+    limit: i64,
+    offset: Option<i64>,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<Vec<FeeRule>>> {
+    let rules: Vec<FeeRule> = sqlx::query_as(
+        r#"
+        SELECT * FROM fee_rules
+        WHERE business_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(auth.api_key.business_id)
+    .bind(query.limit)
+    .bind(query.offset.unwrap_or(0))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(rules))
+}
+
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(req): Json<CreateFeeRuleRequest>,
+) -> Result<impl IntoResponse> {
+    let business_id = auth.api_key.business_id;
+
+    if !matches!(
+        req.transaction_type,
+        TransactionType::Credit | TransactionType::Debit | TransactionType::Transfer
+    ) {
+        return Err(AppError::Validation(
+            "fees apply only to credit, debit and transfer transactions".into(),
+        ));
+    }
+
+    if req.flat_amount < Decimal::ZERO
+        || req.min_amount.is_some_and(|min| min < Decimal::ZERO)
+        || req.max_amount.is_some_and(|max| max < Decimal::ZERO)
+    {
+        return Err(AppError::Validation(
+            "fee amounts must not be negative".into(),
+        ));
+    }
+
+    if req.percentage < Decimal::ZERO || req.percentage > Decimal::ONE_HUNDRED {
+        return Err(AppError::Validation(
+            "percentage must be between 0 and 100".into(),
+        ));
+    }
+
+    if let (Some(min), Some(max)) = (req.min_amount, req.max_amount) {
+        if min > max {
+            return Err(AppError::Validation(
+                "min_amount must not exceed max_amount".into(),
+            ));
+        }
+    }
+
+    let revenue: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1")
+        .bind(req.revenue_account_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "revenue account not found: {}",
+                req.revenue_account_id
+            ))
+        })?;
+
+    if revenue.account_type != AccountType::Revenue
+        || revenue.business_id != business_id
+        || revenue.currency != req.currency
+        || revenue.status == AccountStatus::Closed
+    {
+        return Err(AppError::Validation(
+            "revenue_account_id must be an open revenue account of the same business and currency"
+                .into(),
+        ));
+    }
+
+    let now = Utc::now();
+    let rule: FeeRule = sqlx::query_as(
+        r#"
+        INSERT INTO fee_rules (id, business_id, transaction_type, currency, flat_amount, percentage, min_amount, max_amount, revenue_account_id, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(business_id)
+    .bind(req.transaction_type)
+    .bind(&req.currency)
+    .bind(req.flat_amount)
+    .bind(req.percentage)
+    .bind(req.min_amount)
+    .bind(req.max_amount)
+    .bind(req.revenue_account_id)
+    .bind(now)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.constraint() == Some("fee_rules_unique_type") => {
+            AppError::Validation(
+                "a fee rule already exists for this transaction type and currency".into(),
+            )
+        }
+        _ => AppError::Database(e),
+    })?;
+
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+pub async fn get(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<FeeRule>> {
+    let rule: FeeRule =
+        sqlx::query_as("SELECT * FROM fee_rules WHERE id = $1 AND business_id = $2")
+            .bind(id)
+            .bind(auth.api_key.business_id)
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("fee rule not found: {}", id)))?;
+
+    Ok(Json(rule))
+}
+
+/// Deletes the rule. Fees already charged stay on their transactions.
+pub async fn delete(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let result = sqlx::query("DELETE FROM fee_rules WHERE id = $1 AND business_id = $2")
+        .bind(id)
+        .bind(auth.api_key.business_id)
+        .execute(&state.db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("fee rule not found: {}", id)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
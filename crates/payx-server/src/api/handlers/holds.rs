@@ -0,0 +1,472 @@
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::{Extension, Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::{Duration, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::api::handlers::transactions::{
+    enqueue_webhook, ensure_can_post, ensure_funds, ensure_owned, ensure_within_limits,
+    notify_overdraft,
+};
+use crate::api::middleware::auth::AuthContext;
+use crate::domain::{
+    Account, CaptureHoldRequest, CreateHoldRequest, EntryType, Hold, HoldResponse, HoldStatus,
+    Transaction, TransactionStatus, TransactionType,
+};
+use crate::error::{AppError, Result};
+use crate::ledger;
+use crate::AppState;
+
+pub async fn authorize(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    headers: HeaderMap,
+    Json(req): Json<CreateHoldRequest>,
+) -> Result<impl IntoResponse> {
+    let idempotency_key = headers
+        .get("idempotency-key")
+        .and_then(|h| h.to_str().ok())
+        .map(String::from);
+
+    if req.amount <= Decimal::ZERO {
+        return Err(AppError::Validation("amount must be positive".into()));
+    }
+
+    if req.destination_account_id == Some(req.source_account_id) {
+        return Err(AppError::Validation(
+            "source and destination accounts must differ".into(),
+        ));
+    }
+
+    if let Some(ref key) = idempotency_key {
+        let existing: Option<Hold> = sqlx::query_as(
+            r#"
+            SELECT h.* FROM holds h
+            JOIN transactions t ON t.id = h.transaction_id
+            WHERE t.idempotency_key = $1 AND t.business_id = $2
+            "#,
+        )
+        .bind(key)
+        .bind(auth.api_key.business_id)
+        .fetch_optional(&state.db)
+        .await?;
+
+        if let Some(hold) = existing {
+            return Ok((StatusCode::OK, Json(HoldResponse::from(hold))));
+        }
+    }
+
+    let mut tx = state.db.begin().await?;
+    let now = Utc::now();
+    let txn_id = Uuid::new_v4();
+
+    let source: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1 FOR UPDATE")
+        .bind(req.source_account_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::AccountNotFound(req.source_account_id))?;
+    ensure_owned(&source, auth.api_key.business_id)?;
+
+    if source.currency != req.currency {
+        return Err(AppError::CurrencyMismatch {
+            from_currency: source.currency,
+            to_currency: req.currency.clone(),
+        });
+    }
+
+    if let Some(dest_id) = req.destination_account_id {
+        let dest: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1")
+            .bind(dest_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::AccountNotFound(dest_id))?;
+        ensure_owned(&dest, auth.api_key.business_id)?;
+
+        if dest.currency != req.currency {
+            return Err(AppError::CurrencyMismatch {
+                from_currency: req.currency.clone(),
+                to_currency: dest.currency,
+            });
+        }
+
+        ensure_can_post(&dest, EntryType::Credit)?;
+    }
+
+    if source.is_settlement {
+        return Err(AppError::Validation(
+            "holds cannot be placed on settlement accounts".into(),
+        ));
+    }
+
+    // A hold reserves funds a later debit takes out of the balance
+    if source.account_type.normal_balance() != EntryType::Credit {
+        return Err(AppError::Validation(
+            "holds can only be placed on credit-normal accounts".into(),
+        ));
+    }
+
+    ensure_can_post(&source, EntryType::Debit)?;
+    ensure_funds(&source, EntryType::Debit, req.amount)?;
+    ensure_within_limits(&mut tx, source.id, req.amount, now, None).await?;
+
+    sqlx::query("UPDATE accounts SET available_balance = $1, version = version + 1, updated_at = $2 WHERE id = $3")
+        .bind(source.available_balance - req.amount)
+        .bind(now)
+        .bind(source.id)
+        .execute(&mut *tx)
+        .await?;
+
+    let tx_type = match req.destination_account_id {
+        Some(_) => TransactionType::Transfer,
+        None => TransactionType::Debit,
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO transactions (id, business_id, idempotency_key, type, status, source_account_id, destination_account_id, amount, currency, description, metadata, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        "#,
+    )
+    .bind(txn_id)
+    .bind(auth.api_key.business_id)
+    .bind(idempotency_key.as_deref())
+    .bind(tx_type)
+    .bind(TransactionStatus::Pending)
+    .bind(source.id)
+    .bind(req.destination_account_id)
+    .bind(req.amount)
+    .bind(&req.currency)
+    .bind(&req.description)
+    .bind(&req.metadata)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+
+    let hold: Hold = sqlx::query_as(
+        r#"
+        INSERT INTO holds (id, transaction_id, account_id, destination_account_id, amount, currency, status, expires_at, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(txn_id)
+    .bind(source.id)
+    .bind(req.destination_account_id)
+    .bind(req.amount)
+    .bind(&req.currency)
+    .bind(HoldStatus::Active)
+    .bind(now + Duration::seconds(state.config.hold_ttl_seconds))
+    .bind(now)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    enqueue_webhook(&mut tx, source.business_id, "hold.authorized", &hold).await?;
+
+    tx.commit().await?;
+    Ok((StatusCode::CREATED, Json(HoldResponse::from(hold))))
+}
+
+pub async fn get(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<HoldResponse>> {
+    let hold: Hold = sqlx::query_as(
+        r#"
+        SELECT h.* FROM holds h
+        JOIN accounts a ON a.id = h.account_id
+        WHERE h.id = $1 AND a.business_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(auth.api_key.business_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(AppError::HoldNotFound(id))?;
+
+    Ok(Json(HoldResponse::from(hold)))
+}
+
+pub async fn capture(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+    body: Bytes,
+) -> Result<Json<HoldResponse>> {
+    // An empty body captures the full authorized amount.
+    let req: CaptureHoldRequest = if body.is_empty() {
+        CaptureHoldRequest::default()
+    } else {
+        serde_json::from_slice(&body)
+            .map_err(|e| AppError::Validation(format!("invalid capture request: {}", e)))?
+    };
+
+    let mut tx = state.db.begin().await?;
+    let now = Utc::now();
+
+    let hold: Hold = sqlx::query_as(
+        r#"
+        SELECT h.* FROM holds h
+        JOIN accounts a ON a.id = h.account_id
+        WHERE h.id = $1 AND a.business_id = $2
+        FOR UPDATE OF h
+        "#,
+    )
+    .bind(id)
+    .bind(auth.api_key.business_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::HoldNotFound(id))?;
+
+    if !hold.is_capturable(now) {
+        return Err(AppError::HoldNotActive {
+            hold_id: id,
+            status: match hold.status {
+                HoldStatus::Active => HoldStatus::Expired.as_str().into(),
+                status => status.as_str().into(),
+            },
+        });
+    }
+
+    let amount = req.amount.unwrap_or(hold.amount);
+    if amount <= Decimal::ZERO || amount > hold.amount {
+        return Err(AppError::Validation(format!(
+            "capture amount must be positive and at most {}",
+            hold.amount
+        )));
+    }
+
+    // Debit holds settle against the business's settlement account
+    let counterparty_id = match hold.destination_account_id {
+        Some(dest_id) => dest_id,
+        None => {
+            ledger::settlement_account_id(&mut tx, auth.api_key.business_id, &hold.currency).await?
+        }
+    };
+
+    let accounts = ledger::lock_accounts(&mut tx, &[hold.account_id, counterparty_id]).await?;
+
+    let source = accounts
+        .get(&hold.account_id)
+        .cloned()
+        .ok_or(AppError::AccountNotFound(hold.account_id))?;
+
+    ensure_can_post(&source, EntryType::Debit)?;
+    ensure_within_limits(&mut tx, source.id, amount, now, Some(hold.id)).await?;
+    if let Some(dest) = hold.destination_account_id.and_then(|id| accounts.get(&id)) {
+        ensure_can_post(dest, EntryType::Credit)?;
+        ensure_funds(dest, EntryType::Credit, amount)?;
+    }
+
+    // The full hold was reserved at authorization; release whatever is not captured.
+    let source_new_balance = source.balance - amount;
+    let source_new_available = source.available_balance + (hold.amount - amount);
+
+    sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
+        .bind(source_new_balance)
+        .bind(source_new_available)
+        .bind(now)
+        .bind(source.id)
+        .execute(&mut *tx)
+        .await?;
+
+    let transaction: Transaction = sqlx::query_as(
+        r#"
+        UPDATE transactions
+        SET status = $1, amount = $2, completed_at = $3
+        WHERE id = $4
+        RETURNING *
+        "#,
+    )
+    .bind(TransactionStatus::Completed)
+    .bind(amount)
+    .bind(now)
+    .bind(hold.transaction_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    ledger::post_entry(
+        &mut tx,
+        hold.transaction_id,
+        source.id,
+        EntryType::Debit,
+        amount,
+        source_new_balance,
+        now,
+    )
+    .await?;
+
+    notify_overdraft(
+        &mut tx,
+        &source,
+        source.balance,
+        source_new_balance,
+        hold.transaction_id,
+    )
+    .await?;
+
+    match hold.destination_account_id {
+        Some(dest_id) => {
+            let dest = accounts
+                .get(&dest_id)
+                .cloned()
+                .ok_or(AppError::AccountNotFound(dest_id))?;
+            let delta = dest.balance_delta(EntryType::Credit, amount);
+            let dest_new_balance = dest.balance + delta;
+
+            sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
+                .bind(dest_new_balance)
+                .bind(dest.available_balance + delta)
+                .bind(now)
+                .bind(dest.id)
+                .execute(&mut *tx)
+                .await?;
+
+            ledger::post_entry(
+                &mut tx,
+                hold.transaction_id,
+                dest.id,
+                EntryType::Credit,
+                amount,
+                dest_new_balance,
+                now,
+            )
+            .await?;
+        }
+        None => {
+            let settlement = accounts
+                .get(&counterparty_id)
+                .ok_or(AppError::AccountNotFound(counterparty_id))?;
+
+            ledger::post_settlement(
+                &mut tx,
+                settlement,
+                hold.transaction_id,
+                EntryType::Credit,
+                amount,
+                now,
+            )
+            .await?;
+        }
+    }
+
+    let hold: Hold = sqlx::query_as(
+        r#"
+        UPDATE holds
+        SET status = $1, captured_amount = $2, released_at = $3
+        WHERE id = $4
+        RETURNING *
+        "#,
+    )
+    .bind(HoldStatus::Captured)
+    .bind(amount)
+    .bind(now)
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    enqueue_webhook(&mut tx, source.business_id, "hold.captured", &hold).await?;
+    enqueue_webhook(
+        &mut tx,
+        source.business_id,
+        "transaction.completed",
+        &transaction,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(Json(HoldResponse::from(hold)))
+}
+
+pub async fn void(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<HoldResponse>> {
+    let mut tx = state.db.begin().await?;
+
+    let hold: Hold = sqlx::query_as(
+        r#"
+        SELECT h.* FROM holds h
+        JOIN accounts a ON a.id = h.account_id
+        WHERE h.id = $1 AND a.business_id = $2
+        FOR UPDATE OF h
+        "#,
+    )
+    .bind(id)
+    .bind(auth.api_key.business_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::HoldNotFound(id))?;
+
+    if hold.status != HoldStatus::Active {
+        return Err(AppError::HoldNotActive {
+            hold_id: id,
+            status: hold.status.as_str().into(),
+        });
+    }
+
+    let hold = release_hold(&mut tx, &hold, HoldStatus::Voided).await?;
+
+    tx.commit().await?;
+    Ok(Json(HoldResponse::from(hold)))
+}
+
+/// Returns the reserved amount to the account's available balance and closes
+/// the hold (and its pending transaction) with the given terminal status.
+/// The caller must hold the row lock on `hold`.
+pub(crate) async fn release_hold(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    hold: &Hold,
+    status: HoldStatus,
+) -> Result<Hold> {
+    let (tx_status, event_type) = match status {
+        HoldStatus::Voided => (TransactionStatus::Voided, "hold.voided"),
+        HoldStatus::Expired => (TransactionStatus::Expired, "hold.expired"),
+        HoldStatus::Active | HoldStatus::Captured => {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "hold cannot be released as {:?}",
+                status
+            )))
+        }
+    };
+
+    let now = Utc::now();
+
+    let account: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1 FOR UPDATE")
+        .bind(hold.account_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(AppError::AccountNotFound(hold.account_id))?;
+
+    sqlx::query("UPDATE accounts SET available_balance = $1, version = version + 1, updated_at = $2 WHERE id = $3")
+        .bind(account.available_balance + hold.amount)
+        .bind(now)
+        .bind(account.id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query("UPDATE transactions SET status = $1 WHERE id = $2")
+        .bind(tx_status)
+        .bind(hold.transaction_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let hold: Hold =
+        sqlx::query_as("UPDATE holds SET status = $1, released_at = $2 WHERE id = $3 RETURNING *")
+            .bind(status)
+            .bind(now)
+            .bind(hold.id)
+            .fetch_one(&mut **tx)
+            .await?;
+
+    enqueue_webhook(tx, account.business_id, event_type, &hold).await?;
+
+    Ok(hold)
+}
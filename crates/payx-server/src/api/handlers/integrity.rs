@@ -0,0 +1,18 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, State};
+use axum::Json;
+
+use crate::api::middleware::auth::AuthContext;
+use crate::domain::IntegrityReport;
+use crate::error::Result;
+use crate::workers::integrity_checker::run_checks;
+use crate::AppState;
+
+pub async fn report(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<IntegrityReport>> {
+    let report = run_checks(&state.db, Some(auth.api_key.business_id)).await?;
+    Ok(Json(report))
+}
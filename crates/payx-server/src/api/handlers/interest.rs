@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Path, Query, State};
+use axum::Json;
+use chrono::{Days, Utc};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::handlers::accounts::find_account;
+use crate::api::middleware::auth::AuthContext;
+use crate::domain::{
+    Account, AccountInterest, AccountStatus, AccountType, EntryType, InterestAccrual,
+    SetAccountInterestRequest,
+};
+use crate::error::{AppError, Result};
+use crate::AppState;
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    #[serde(default = "default_limit")]
+    limit: i64,
+    offset: Option<i64>,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+pub async fn get(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(account_id): Path<Uuid>,
+) -> Result<Json<AccountInterest>> {
+    find_account(&state.db, auth.api_key.business_id, account_id).await?;
+
+    let interest: AccountInterest =
+        sqlx::query_as("SELECT * FROM account_interest WHERE account_id = $1")
+            .bind(account_id)
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "interest not configured for account {}",
+                    account_id
+                ))
+            })?;
+
+    Ok(Json(interest))
+}
+
+/// Sets the account's rate and expense account. Accrual starts on the day
+/// interest is first configured; a changed rate applies from the next day
+/// accrued.
+pub async fn set(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(account_id): Path<Uuid>,
+    Json(req): Json<SetAccountInterestRequest>,
+) -> Result<Json<AccountInterest>> {
+    if req.annual_rate < Decimal::ZERO {
+        return Err(AppError::Validation(
+            "annual_rate must not be negative".into(),
+        ));
+    }
+
+    let account = find_account(&state.db, auth.api_key.business_id, account_id).await?;
+
+    if account.is_settlement || account.account_type.normal_balance() != EntryType::Credit {
+        return Err(AppError::Validation(
+            "interest can only accrue on credit-normal accounts".into(),
+        ));
+    }
+
+    let expense: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1")
+        .bind(req.expense_account_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "expense account not found: {}",
+                req.expense_account_id
+            ))
+        })?;
+
+    if expense.account_type != AccountType::Expense
+        || expense.business_id != account.business_id
+        || expense.currency != account.currency
+        || expense.status == AccountStatus::Closed
+    {
+        return Err(AppError::Validation(
+            "expense_account_id must be an open expense account of the same business and currency"
+                .into(),
+        ));
+    }
+
+    // Accrual starts on the business's current day
+    let timezone: String = sqlx::query_scalar("SELECT timezone FROM businesses WHERE id = $1")
+        .bind(account.business_id)
+        .fetch_one(&state.db)
+        .await?;
+    let tz = timezone.parse::<Tz>().unwrap_or(Tz::UTC);
+    let now = Utc::now();
+    let yesterday = now.with_timezone(&tz).date_naive() - Days::new(1);
+
+    let interest: AccountInterest = sqlx::query_as(
+        r#"
+        INSERT INTO account_interest (account_id, annual_rate, expense_account_id, accrued_through, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $5)
+        ON CONFLICT (account_id) DO UPDATE SET
+            annual_rate = EXCLUDED.annual_rate,
+            expense_account_id = EXCLUDED.expense_account_id,
+            updated_at = EXCLUDED.updated_at
+        RETURNING *
+        "#,
+    )
+    .bind(account_id)
+    .bind(req.annual_rate)
+    .bind(req.expense_account_id)
+    .bind(yesterday)
+    .bind(now)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(interest))
+}
+
+pub async fn list_accruals(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(account_id): Path<Uuid>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<Vec<InterestAccrual>>> {
+    find_account(&state.db, auth.api_key.business_id, account_id).await?;
+
+    let accruals: Vec<InterestAccrual> = sqlx::query_as(
+        r#"
+        SELECT * FROM interest_accruals
+        WHERE account_id = $1
+        ORDER BY accrual_date DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(account_id)
+    .bind(query.limit)
+    .bind(query.offset.unwrap_or(0))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(accruals))
+}
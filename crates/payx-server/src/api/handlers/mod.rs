@@ -1,5 +1,10 @@
 pub mod accounts;
+pub mod api_keys;
 pub mod businesses;
+pub mod fees;
 pub mod health;
+pub mod holds;
+pub mod integrity;
+pub mod interest;
 pub mod transactions;
 pub mod webhooks;
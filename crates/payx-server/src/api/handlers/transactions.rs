@@ -1,19 +1,27 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
-use axum::extract::{Path, Query, State};
+use axum::body::Bytes;
+use axum::extract::{Extension, Path, Query, State};
 use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use axum::Json;
-use chrono::Utc;
-use rust_decimal::Decimal;
-use serde::Deserialize;
+use chrono::{DateTime, Datelike, NaiveTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::api::handlers::accounts::find_account;
+use crate::api::middleware::auth::AuthContext;
 use crate::domain::{
-    Account, CreateTransactionRequest, Transaction, TransactionResponse, TransactionStatus,
-    TransactionType, WebhookPayload,
+    Account, AccountLimits, AccountOverdrawnEvent, BatchItemResult, BatchMode,
+    BatchTransactionItem, BatchTransactionRequest, BatchTransactionResponse,
+    CreateTransactionRequest, EntryType, FeeRule, LedgerEntry, ReverseTransactionRequest,
+    Transaction, TransactionFee, TransactionResponse, TransactionStatus, TransactionType,
+    WebhookPayload,
 };
 use crate::error::{AppError, Result};
+use crate::ledger;
 use crate::AppState;
 
 #[derive(Deserialize)]
@@ -30,14 +38,19 @@ fn default_limit() -> i64 {
 
 pub async fn list(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Query(query): Query<ListQuery>,
 ) -> Result<Json<Vec<TransactionResponse>>> {
+    let business_id = auth.api_key.business_id;
+
     let transactions: Vec<Transaction> = match query.account_id {
         Some(account_id) => {
+            find_account(&state.db, business_id, account_id).await?;
             sqlx::query_as(
                 r#"
                 SELECT * FROM transactions
                 WHERE source_account_id = $1 OR destination_account_id = $1
+                OR id IN (SELECT transaction_id FROM ledger_entries WHERE account_id = $1)
                 ORDER BY created_at DESC
                 LIMIT $2 OFFSET $3
                 "#,
@@ -49,11 +62,19 @@ pub async fn list(
             .await?
         }
         None => {
-            sqlx::query_as("SELECT * FROM transactions ORDER BY created_at DESC LIMIT $1 OFFSET $2")
-                .bind(query.limit)
-                .bind(query.offset.unwrap_or(0))
-                .fetch_all(&state.db)
-                .await?
+            sqlx::query_as(
+                r#"
+                SELECT * FROM transactions
+                WHERE business_id = $1
+                ORDER BY created_at DESC
+                LIMIT $2 OFFSET $3
+                "#,
+            )
+            .bind(business_id)
+            .bind(query.limit)
+            .bind(query.offset.unwrap_or(0))
+            .fetch_all(&state.db)
+            .await?
         }
     };
 
@@ -67,6 +88,7 @@ pub async fn list(
 
 pub async fn create(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     headers: HeaderMap,
     Json(req): Json<CreateTransactionRequest>,
 ) -> Result<impl IntoResponse> {
@@ -75,23 +97,18 @@ pub async fn create(
         .and_then(|h| h.to_str().ok())
         .map(String::from);
 
-    if req.amount <= Decimal::ZERO {
-        return Err(AppError::Validation("amount must be positive".into()));
-    }
+    validate(&req)?;
 
+    let business_id = auth.api_key.business_id;
     if let Some(ref key) = idempotency_key {
-        if let Some(existing) = find_by_idempotency_key(&state, key).await? {
+        if let Some(existing) = find_by_idempotency_key(&state, business_id, key).await? {
             return Ok((StatusCode::OK, Json(TransactionResponse::from(existing))));
         }
     }
 
-    let transaction = match req.tx_type {
-        TransactionType::Credit => execute_credit(&state, &req, idempotency_key.as_deref()).await?,
-        TransactionType::Debit => execute_debit(&state, &req, idempotency_key.as_deref()).await?,
-        TransactionType::Transfer => {
-            execute_transfer(&state, &req, idempotency_key.as_deref()).await?
-        }
-    };
+    let mut tx = state.db.begin().await?;
+    let transaction = execute(&mut tx, business_id, &req, idempotency_key.as_deref()).await?;
+    tx.commit().await?;
 
     Ok((
         StatusCode::CREATED,
@@ -99,30 +116,337 @@ pub async fn create(
     ))
 }
 
+pub async fn batch(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(req): Json<BatchTransactionRequest>,
+) -> Result<Json<BatchTransactionResponse>> {
+    if req.transactions.is_empty() || req.transactions.len() > state.config.batch_max_items {
+        return Err(AppError::Validation(format!(
+            "a batch must contain between 1 and {} transactions",
+            state.config.batch_max_items
+        )));
+    }
+
+    let mut keys = HashSet::new();
+    for item in &req.transactions {
+        if let Some(ref key) = item.idempotency_key {
+            if !keys.insert(key) {
+                return Err(AppError::Validation(format!(
+                    "duplicate idempotency key in batch: {}",
+                    key
+                )));
+            }
+        }
+    }
+
+    let results = match req.mode {
+        BatchMode::Atomic => execute_atomic_batch(&state, &auth, &req.transactions).await?,
+        BatchMode::Independent => execute_independent_batch(&state, &auth, &req.transactions).await,
+    };
+
+    Ok(Json(BatchTransactionResponse {
+        mode: req.mode,
+        results,
+    }))
+}
+
+/// Posts every item in one database transaction; the first failure rolls
+/// back the whole batch.
+async fn execute_atomic_batch(
+    state: &AppState,
+    auth: &AuthContext,
+    items: &[BatchTransactionItem],
+) -> Result<Vec<BatchItemResult>> {
+    let business_id = auth.api_key.business_id;
+    let failed = |index, error| AppError::BatchItemFailed {
+        index,
+        error: Box::new(error),
+    };
+
+    for (index, item) in items.iter().enumerate() {
+        validate(&item.request).map_err(|e| failed(index, e))?;
+    }
+
+    let mut tx = state.db.begin().await?;
+    lock_batch_accounts(&mut tx, business_id, items).await?;
+
+    let mut results = Vec::with_capacity(items.len());
+    for (index, item) in items.iter().enumerate() {
+        let key = item.idempotency_key.as_deref();
+
+        if let Some(key) = key {
+            if let Some(existing) = find_by_idempotency_key(state, business_id, key).await? {
+                results.push(BatchItemResult {
+                    index,
+                    status: StatusCode::OK.as_u16(),
+                    transaction: Some(TransactionResponse::from(existing)),
+                    error: None,
+                });
+                continue;
+            }
+        }
+
+        let transaction = execute(&mut tx, business_id, &item.request, key)
+            .await
+            .map_err(|e| failed(index, e))?;
+        results.push(BatchItemResult {
+            index,
+            status: StatusCode::CREATED.as_u16(),
+            transaction: Some(TransactionResponse::from(transaction)),
+            error: None,
+        });
+    }
+
+    tx.commit().await?;
+    Ok(results)
+}
+
+/// Posts each item in its own database transaction and reports per-item
+/// outcomes.
+async fn execute_independent_batch(
+    state: &AppState,
+    auth: &AuthContext,
+    items: &[BatchTransactionItem],
+) -> Vec<BatchItemResult> {
+    let mut results = Vec::with_capacity(items.len());
+    for (index, item) in items.iter().enumerate() {
+        let result = match execute_batch_item(state, auth.api_key.business_id, item).await {
+            Ok((status, transaction)) => BatchItemResult {
+                index,
+                status: status.as_u16(),
+                transaction: Some(TransactionResponse::from(transaction)),
+                error: None,
+            },
+            Err(e) => BatchItemResult {
+                index,
+                status: e.status().as_u16(),
+                transaction: None,
+                error: Some(e.body()),
+            },
+        };
+        results.push(result);
+    }
+    results
+}
+
+async fn execute_batch_item(
+    state: &AppState,
+    business_id: Uuid,
+    item: &BatchTransactionItem,
+) -> Result<(StatusCode, Transaction)> {
+    validate(&item.request)?;
+
+    let key = item.idempotency_key.as_deref();
+    if let Some(key) = key {
+        if let Some(existing) = find_by_idempotency_key(state, business_id, key).await? {
+            return Ok((StatusCode::OK, existing));
+        }
+    }
+
+    let mut tx = state.db.begin().await?;
+    let transaction = execute(&mut tx, business_id, &item.request, key).await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, transaction))
+}
+
+/// Locks every account an atomic batch can touch, including the settlement
+/// accounts of its credits and debits and the revenue accounts of its fees,
+/// so that batch items never wait on each other's locks in an inconsistent
+/// order. Other businesses' accounts are left for the items to reject.
+async fn lock_batch_accounts(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    business_id: Uuid,
+    items: &[BatchTransactionItem],
+) -> Result<()> {
+    let mut account_ids = Vec::new();
+    let mut settled_ids = Vec::new();
+    for item in items {
+        let req = &item.request;
+        account_ids.extend(req.source_account_id);
+        account_ids.extend(req.destination_account_id);
+        account_ids.extend(req.legs.iter().map(|l| l.account_id));
+        match req.tx_type {
+            TransactionType::Credit => settled_ids.extend(req.destination_account_id),
+            TransactionType::Debit => settled_ids.extend(req.source_account_id),
+            _ => {}
+        }
+    }
+
+    let owners: Vec<(Uuid, String)> = sqlx::query_as(
+        "SELECT DISTINCT business_id, currency FROM accounts WHERE id = ANY($1) AND business_id = $2 AND NOT is_settlement",
+    )
+    .bind(&settled_ids)
+    .bind(business_id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    for (business_id, currency) in owners {
+        account_ids.push(ledger::settlement_account_id(tx, business_id, &currency).await?);
+    }
+
+    // Revenue accounts that any item may pay a fee into
+    let revenue_ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT f.revenue_account_id FROM fee_rules f
+        JOIN accounts a ON a.business_id = f.business_id AND a.currency = f.currency
+        WHERE a.id = ANY($1)
+        "#,
+    )
+    .bind(&account_ids)
+    .fetch_all(&mut **tx)
+    .await?;
+    account_ids.extend(revenue_ids);
+
+    // Missing accounts are reported by the item that references them
+    sqlx::query(
+        "SELECT id FROM accounts WHERE id = ANY($1) AND business_id = $2 ORDER BY id FOR UPDATE",
+    )
+    .bind(&account_ids)
+    .bind(business_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
 pub async fn get(
     State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<TransactionResponse>> {
-    let transaction: Transaction = sqlx::query_as("SELECT * FROM transactions WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.db)
-        .await?
-        .ok_or(AppError::TransactionNotFound(id))?;
+    let transaction: Transaction = sqlx::query_as(
+        r#"
+        SELECT * FROM transactions
+        WHERE id = $1 AND business_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(auth.api_key.business_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(AppError::TransactionNotFound(id))?;
 
     Ok(Json(TransactionResponse::from(transaction)))
 }
 
-async fn find_by_idempotency_key(state: &AppState, key: &str) -> Result<Option<Transaction>> {
-    let txn: Option<Transaction> =
-        sqlx::query_as("SELECT * FROM transactions WHERE idempotency_key = $1")
-            .bind(key)
-            .fetch_optional(&state.db)
-            .await?;
+pub async fn reverse(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse> {
+    let idempotency_key = headers
+        .get("idempotency-key")
+        .and_then(|h| h.to_str().ok())
+        .map(String::from);
+
+    // An empty body reverses whatever has not been refunded yet.
+    let req: ReverseTransactionRequest = if body.is_empty() {
+        ReverseTransactionRequest::default()
+    } else {
+        serde_json::from_slice(&body)
+            .map_err(|e| AppError::Validation(format!("invalid reverse request: {}", e)))?
+    };
+
+    let business_id = auth.api_key.business_id;
+    if let Some(ref key) = idempotency_key {
+        if let Some(existing) = find_by_idempotency_key(&state, business_id, key).await? {
+            return Ok((StatusCode::OK, Json(TransactionResponse::from(existing))));
+        }
+    }
+
+    let mut tx = state.db.begin().await?;
+    let transaction =
+        execute_reversal(&mut tx, business_id, id, &req, idempotency_key.as_deref()).await?;
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(TransactionResponse::from(transaction)),
+    ))
+}
+
+async fn find_by_idempotency_key(
+    state: &AppState,
+    business_id: Uuid,
+    key: &str,
+) -> Result<Option<Transaction>> {
+    let txn: Option<Transaction> = sqlx::query_as(
+        r#"
+        SELECT * FROM transactions
+        WHERE idempotency_key = $1 AND business_id = $2
+        "#,
+    )
+    .bind(key)
+    .bind(business_id)
+    .fetch_optional(&state.db)
+    .await?;
     Ok(txn)
 }
 
+fn validate(req: &CreateTransactionRequest) -> Result<()> {
+    if req.amount <= Decimal::ZERO {
+        return Err(AppError::Validation("amount must be positive".into()));
+    }
+
+    if !req.legs.is_empty() && req.tx_type != TransactionType::Journal {
+        return Err(AppError::Validation(
+            "legs are only allowed for journal transactions".into(),
+        ));
+    }
+
+    if req.tx_type == TransactionType::Reversal {
+        return Err(AppError::Validation(
+            "reversals are created via POST /v1/transactions/:id/reverse".into(),
+        ));
+    }
+
+    if req.tx_type == TransactionType::Opening {
+        return Err(AppError::Validation(
+            "opening transactions are posted when an account is created".into(),
+        ));
+    }
+
+    if req.tx_type == TransactionType::Interest {
+        return Err(AppError::Validation(
+            "interest transactions are posted by interest accrual".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Posts a validated request for `business_id` within the caller's database
+/// transaction.
+async fn execute(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    business_id: Uuid,
+    req: &CreateTransactionRequest,
+    idempotency_key: Option<&str>,
+) -> Result<Transaction> {
+    match req.tx_type {
+        TransactionType::Credit => execute_credit(tx, business_id, req, idempotency_key).await,
+        TransactionType::Debit => execute_debit(tx, business_id, req, idempotency_key).await,
+        TransactionType::Transfer => execute_transfer(tx, business_id, req, idempotency_key).await,
+        TransactionType::Journal => execute_journal(tx, business_id, req, idempotency_key).await,
+        TransactionType::Reversal => Err(AppError::Validation(
+            "reversals are created via POST /v1/transactions/:id/reverse".into(),
+        )),
+        TransactionType::Opening => Err(AppError::Validation(
+            "opening transactions are posted when an account is created".into(),
+        )),
+        TransactionType::Interest => Err(AppError::Validation(
+            "interest transactions are posted by interest accrual".into(),
+        )),
+    }
+}
+
 async fn execute_credit(
-    state: &AppState,
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    business_id: Uuid,
     req: &CreateTransactionRequest,
     idempotency_key: Option<&str>,
 ) -> Result<Transaction> {
@@ -130,15 +454,15 @@ async fn execute_credit(
         .destination_account_id
         .ok_or_else(|| AppError::Validation("destination_account_id required for credit".into()))?;
 
-    let mut tx = state.db.begin().await?;
     let now = Utc::now();
     let txn_id = Uuid::new_v4();
 
-    let dest: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1 FOR UPDATE")
+    let dest: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1")
         .bind(dest_id)
-        .fetch_optional(&mut *tx)
+        .fetch_optional(&mut **tx)
         .await?
         .ok_or(AppError::AccountNotFound(dest_id))?;
+    ensure_owned(&dest, business_id)?;
 
     if dest.currency != req.currency {
         return Err(AppError::CurrencyMismatch {
@@ -147,23 +471,52 @@ async fn execute_credit(
         });
     }
 
-    let new_balance = dest.balance + req.amount;
+    if dest.is_settlement {
+        return Err(AppError::Validation(
+            "settlement accounts cannot be credited directly".into(),
+        ));
+    }
+
+    // The business's settlement account funds the credit
+    let settlement_id = ledger::settlement_account_id(tx, dest.business_id, &req.currency).await?;
+    let fee = find_fee_rule(tx, dest.business_id, TransactionType::Credit, &req.currency)
+        .await?
+        .map(|rule| rule.charge(dest_id, req.amount));
+
+    let mut account_ids = vec![dest_id, settlement_id];
+    account_ids.extend(fee.as_ref().map(|f| f.revenue_account_id));
+    let mut accounts = ledger::lock_accounts(tx, &account_ids).await?;
+    let dest = accounts
+        .remove(&dest_id)
+        .ok_or(AppError::AccountNotFound(dest_id))?;
+    let settlement = accounts
+        .remove(&settlement_id)
+        .ok_or(AppError::AccountNotFound(settlement_id))?;
+
+    ensure_can_post(&dest, EntryType::Credit)?;
+    ensure_funds(&dest, EntryType::Credit, req.amount)?;
+
+    let delta = dest.balance_delta(EntryType::Credit, req.amount);
+    let new_balance = dest.balance + delta;
+    let new_available = dest.available_balance + delta;
 
-    sqlx::query("UPDATE accounts SET balance = $1, available_balance = $1, version = version + 1, updated_at = $2 WHERE id = $3")
+    sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
         .bind(new_balance)
+        .bind(new_available)
         .bind(now)
         .bind(dest_id)
-        .execute(&mut *tx)
+        .execute(&mut **tx)
         .await?;
 
     let transaction: Transaction = sqlx::query_as(
         r#"
-        INSERT INTO transactions (id, idempotency_key, type, status, destination_account_id, amount, currency, description, metadata, created_at, completed_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+        INSERT INTO transactions (id, business_id, idempotency_key, type, status, destination_account_id, amount, currency, description, metadata, created_at, completed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11)
         RETURNING *
         "#,
     )
     .bind(txn_id)
+    .bind(business_id)
     .bind(idempotency_key)
     .bind(TransactionType::Credit)
     .bind(TransactionStatus::Completed)
@@ -173,38 +526,32 @@ async fn execute_credit(
     .bind(&req.description)
     .bind(&req.metadata)
     .bind(now)
-    .fetch_one(&mut *tx)
+    .fetch_one(&mut **tx)
     .await?;
 
-    sqlx::query(
-        r#"
-        INSERT INTO ledger_entries (id, transaction_id, account_id, entry_type, amount, balance_after, created_at)
-        VALUES ($1, $2, $3, 'credit', $4, $5, $6)
-        "#,
+    ledger::post_entry(
+        tx,
+        txn_id,
+        dest_id,
+        EntryType::Credit,
+        req.amount,
+        new_balance,
+        now,
     )
-    .bind(Uuid::new_v4())
-    .bind(txn_id)
-    .bind(dest_id)
-    .bind(req.amount)
-    .bind(new_balance)
-    .bind(now)
-    .execute(&mut *tx)
     .await?;
 
-    enqueue_webhook(
-        &mut tx,
-        dest.business_id,
-        "transaction.completed",
-        &transaction,
-    )
-    .await?;
+    ledger::post_settlement(tx, &settlement, txn_id, EntryType::Debit, req.amount, now).await?;
+
+    let transaction = charge_fee(tx, transaction, fee, now).await?;
+
+    enqueue_webhook(tx, dest.business_id, "transaction.completed", &transaction).await?;
 
-    tx.commit().await?;
     Ok(transaction)
 }
 
 async fn execute_debit(
-    state: &AppState,
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    business_id: Uuid,
     req: &CreateTransactionRequest,
     idempotency_key: Option<&str>,
 ) -> Result<Transaction> {
@@ -212,15 +559,15 @@ async fn execute_debit(
         .source_account_id
         .ok_or_else(|| AppError::Validation("source_account_id required for debit".into()))?;
 
-    let mut tx = state.db.begin().await?;
     let now = Utc::now();
     let txn_id = Uuid::new_v4();
 
-    let source: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1 FOR UPDATE")
+    let source: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1")
         .bind(source_id)
-        .fetch_optional(&mut *tx)
+        .fetch_optional(&mut **tx)
         .await?
         .ok_or(AppError::AccountNotFound(source_id))?;
+    ensure_owned(&source, business_id)?;
 
     if source.currency != req.currency {
         return Err(AppError::CurrencyMismatch {
@@ -229,31 +576,59 @@ async fn execute_debit(
         });
     }
 
-    if source.available_balance < req.amount {
-        return Err(AppError::InsufficientFunds {
-            account_id: source_id,
-            available: source.available_balance,
-            requested: req.amount,
-        });
+    if source.is_settlement {
+        return Err(AppError::Validation(
+            "settlement accounts cannot be debited directly".into(),
+        ));
     }
 
-    let new_balance = source.balance - req.amount;
+    // The business's settlement account receives the debit
+    let settlement_id =
+        ledger::settlement_account_id(tx, source.business_id, &req.currency).await?;
+    let fee = find_fee_rule(
+        tx,
+        source.business_id,
+        TransactionType::Debit,
+        &req.currency,
+    )
+    .await?
+    .map(|rule| rule.charge(source_id, req.amount));
 
-    sqlx::query("UPDATE accounts SET balance = $1, available_balance = $1, version = version + 1, updated_at = $2 WHERE id = $3")
+    let mut account_ids = vec![source_id, settlement_id];
+    account_ids.extend(fee.as_ref().map(|f| f.revenue_account_id));
+    let mut accounts = ledger::lock_accounts(tx, &account_ids).await?;
+    let source = accounts
+        .remove(&source_id)
+        .ok_or(AppError::AccountNotFound(source_id))?;
+    let settlement = accounts
+        .remove(&settlement_id)
+        .ok_or(AppError::AccountNotFound(settlement_id))?;
+
+    ensure_can_post(&source, EntryType::Debit)?;
+    ensure_funds(&source, EntryType::Debit, req.amount + fee_amount(&fee))?;
+    ensure_within_limits(tx, source_id, req.amount + fee_amount(&fee), now, None).await?;
+
+    let delta = source.balance_delta(EntryType::Debit, req.amount);
+    let new_balance = source.balance + delta;
+    let new_available = source.available_balance + delta;
+
+    sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
         .bind(new_balance)
+        .bind(new_available)
         .bind(now)
         .bind(source_id)
-        .execute(&mut *tx)
+        .execute(&mut **tx)
         .await?;
 
     let transaction: Transaction = sqlx::query_as(
         r#"
-        INSERT INTO transactions (id, idempotency_key, type, status, source_account_id, amount, currency, description, metadata, created_at, completed_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+        INSERT INTO transactions (id, business_id, idempotency_key, type, status, source_account_id, amount, currency, description, metadata, created_at, completed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11)
         RETURNING *
         "#,
     )
     .bind(txn_id)
+    .bind(business_id)
     .bind(idempotency_key)
     .bind(TransactionType::Debit)
     .bind(TransactionStatus::Completed)
@@ -263,38 +638,40 @@ async fn execute_debit(
     .bind(&req.description)
     .bind(&req.metadata)
     .bind(now)
-    .fetch_one(&mut *tx)
+    .fetch_one(&mut **tx)
     .await?;
 
-    sqlx::query(
-        r#"
-        INSERT INTO ledger_entries (id, transaction_id, account_id, entry_type, amount, balance_after, created_at)
-        VALUES ($1, $2, $3, 'debit', $4, $5, $6)
-        "#,
+    ledger::post_entry(
+        tx,
+        txn_id,
+        source_id,
+        EntryType::Debit,
+        req.amount,
+        new_balance,
+        now,
     )
-    .bind(Uuid::new_v4())
-    .bind(txn_id)
-    .bind(source_id)
-    .bind(req.amount)
-    .bind(new_balance)
-    .bind(now)
-    .execute(&mut *tx)
     .await?;
 
+    ledger::post_settlement(tx, &settlement, txn_id, EntryType::Credit, req.amount, now).await?;
+
+    notify_overdraft(tx, &source, source.balance, new_balance, txn_id).await?;
+
+    let transaction = charge_fee(tx, transaction, fee, now).await?;
+
     enqueue_webhook(
-        &mut tx,
+        tx,
         source.business_id,
         "transaction.completed",
         &transaction,
     )
     .await?;
 
-    tx.commit().await?;
     Ok(transaction)
 }
 
 async fn execute_transfer(
-    state: &AppState,
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    business_id: Uuid,
     req: &CreateTransactionRequest,
     idempotency_key: Option<&str>,
 ) -> Result<Transaction> {
@@ -305,34 +682,35 @@ async fn execute_transfer(
         AppError::Validation("destination_account_id required for transfer".into())
     })?;
 
-    let mut tx = state.db.begin().await?;
     let now = Utc::now();
     let txn_id = Uuid::new_v4();
 
-    // Lock in consistent order to prevent deadlocks
-    let (first_id, second_id) = if source_id < dest_id {
-        (source_id, dest_id)
-    } else {
-        (dest_id, source_id)
-    };
-
-    let first: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1 FOR UPDATE")
-        .bind(first_id)
-        .fetch_optional(&mut *tx)
-        .await?
-        .ok_or(AppError::AccountNotFound(first_id))?;
-
-    let second: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1 FOR UPDATE")
-        .bind(second_id)
-        .fetch_optional(&mut *tx)
+    // The source pays the fee; its business's rule applies
+    let source: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1")
+        .bind(source_id)
+        .fetch_optional(&mut **tx)
         .await?
-        .ok_or(AppError::AccountNotFound(second_id))?;
+        .ok_or(AppError::AccountNotFound(source_id))?;
+    ensure_owned(&source, business_id)?;
+    let fee = find_fee_rule(
+        tx,
+        source.business_id,
+        TransactionType::Transfer,
+        &req.currency,
+    )
+    .await?
+    .map(|rule| rule.charge(source_id, req.amount));
 
-    let (source, dest) = if first_id == source_id {
-        (first, second)
-    } else {
-        (second, first)
-    };
+    let mut account_ids = vec![source_id, dest_id];
+    account_ids.extend(fee.as_ref().map(|f| f.revenue_account_id));
+    let mut accounts = ledger::lock_accounts(tx, &account_ids).await?;
+    let source = accounts
+        .remove(&source_id)
+        .ok_or(AppError::AccountNotFound(source_id))?;
+    let dest = accounts
+        .remove(&dest_id)
+        .ok_or(AppError::AccountNotFound(dest_id))?;
+    ensure_owned(&dest, business_id)?;
 
     if source.currency != req.currency {
         return Err(AppError::CurrencyMismatch {
@@ -348,39 +726,49 @@ async fn execute_transfer(
         });
     }
 
-    if source.available_balance < req.amount {
-        return Err(AppError::InsufficientFunds {
-            account_id: source_id,
-            available: source.available_balance,
-            requested: req.amount,
-        });
+    // Settlement accounts only move through credits and debits
+    if source.is_settlement || dest.is_settlement {
+        return Err(AppError::Validation(
+            "settlement accounts cannot be transferred to or from".into(),
+        ));
     }
 
-    let source_new_balance = source.balance - req.amount;
-    let dest_new_balance = dest.balance + req.amount;
+    ensure_can_post(&source, EntryType::Debit)?;
+    ensure_can_post(&dest, EntryType::Credit)?;
+    ensure_funds(&source, EntryType::Debit, req.amount + fee_amount(&fee))?;
+    ensure_funds(&dest, EntryType::Credit, req.amount)?;
+    ensure_within_limits(tx, source_id, req.amount + fee_amount(&fee), now, None).await?;
+
+    let source_delta = source.balance_delta(EntryType::Debit, req.amount);
+    let dest_delta = dest.balance_delta(EntryType::Credit, req.amount);
+    let source_new_balance = source.balance + source_delta;
+    let dest_new_balance = dest.balance + dest_delta;
 
-    sqlx::query("UPDATE accounts SET balance = $1, available_balance = $1, version = version + 1, updated_at = $2 WHERE id = $3")
+    sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
         .bind(source_new_balance)
+        .bind(source.available_balance + source_delta)
         .bind(now)
         .bind(source_id)
-        .execute(&mut *tx)
+        .execute(&mut **tx)
         .await?;
 
-    sqlx::query("UPDATE accounts SET balance = $1, available_balance = $1, version = version + 1, updated_at = $2 WHERE id = $3")
+    sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
         .bind(dest_new_balance)
+        .bind(dest.available_balance + dest_delta)
         .bind(now)
         .bind(dest_id)
-        .execute(&mut *tx)
+        .execute(&mut **tx)
         .await?;
 
     let transaction: Transaction = sqlx::query_as(
         r#"
-        INSERT INTO transactions (id, idempotency_key, type, status, source_account_id, destination_account_id, amount, currency, description, metadata, created_at, completed_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11)
+        INSERT INTO transactions (id, business_id, idempotency_key, type, status, source_account_id, destination_account_id, amount, currency, description, metadata, created_at, completed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $12)
         RETURNING *
         "#,
     )
     .bind(txn_id)
+    .bind(business_id)
     .bind(idempotency_key)
     .bind(TransactionType::Transfer)
     .bind(TransactionStatus::Completed)
@@ -391,67 +779,676 @@ async fn execute_transfer(
     .bind(&req.description)
     .bind(&req.metadata)
     .bind(now)
-    .fetch_one(&mut *tx)
+    .fetch_one(&mut **tx)
     .await?;
 
-    sqlx::query(
+    ledger::post_entry(
+        tx,
+        txn_id,
+        source_id,
+        EntryType::Debit,
+        req.amount,
+        source_new_balance,
+        now,
+    )
+    .await?;
+
+    ledger::post_entry(
+        tx,
+        txn_id,
+        dest_id,
+        EntryType::Credit,
+        req.amount,
+        dest_new_balance,
+        now,
+    )
+    .await?;
+
+    notify_overdraft(tx, &source, source.balance, source_new_balance, txn_id).await?;
+
+    let transaction = charge_fee(tx, transaction, fee, now).await?;
+
+    enqueue_webhook(tx, business_id, "transaction.completed", &transaction).await?;
+
+    Ok(transaction)
+}
+
+async fn execute_journal(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    business_id: Uuid,
+    req: &CreateTransactionRequest,
+    idempotency_key: Option<&str>,
+) -> Result<Transaction> {
+    if req.source_account_id.is_some() || req.destination_account_id.is_some() {
+        return Err(AppError::Validation(
+            "journal transactions take legs instead of source and destination accounts".into(),
+        ));
+    }
+
+    if req.legs.len() < 2 {
+        return Err(AppError::Validation(
+            "journal transactions need at least two legs".into(),
+        ));
+    }
+
+    // Debit and credit totals per currency must balance
+    let mut totals: HashMap<&str, (Decimal, Decimal)> = HashMap::new();
+    for leg in &req.legs {
+        if leg.amount <= Decimal::ZERO {
+            return Err(AppError::Validation("leg amounts must be positive".into()));
+        }
+        let currency = leg.currency.as_deref().unwrap_or(&req.currency);
+        let (debits, credits) = totals.entry(currency).or_default();
+        match leg.entry_type {
+            EntryType::Debit => *debits += leg.amount,
+            EntryType::Credit => *credits += leg.amount,
+        }
+    }
+
+    for (currency, (debits, credits)) in &totals {
+        if debits != credits {
+            return Err(AppError::Validation(format!(
+                "unbalanced journal: {} debits {} do not equal credits {}",
+                currency, debits, credits
+            )));
+        }
+    }
+
+    let control_total = totals
+        .get(req.currency.as_str())
+        .map(|(debits, _)| *debits)
+        .unwrap_or_default();
+    if control_total != req.amount {
+        return Err(AppError::Validation(format!(
+            "amount must equal the {} debit total {}",
+            req.currency, control_total
+        )));
+    }
+
+    let now = Utc::now();
+    let txn_id = Uuid::new_v4();
+
+    let account_ids: Vec<Uuid> = req.legs.iter().map(|l| l.account_id).collect();
+    let mut accounts = ledger::lock_accounts(tx, &account_ids).await?;
+    for account in accounts.values() {
+        ensure_owned(account, business_id)?;
+        if account.is_settlement {
+            return Err(AppError::Validation(format!(
+                "settlement accounts cannot be journal legs: {}",
+                account.id
+            )));
+        }
+    }
+    let previous_balances: HashMap<Uuid, Decimal> =
+        accounts.iter().map(|(id, a)| (*id, a.balance)).collect();
+
+    // Each debited account is checked once, against all of its debit legs
+    let mut debits: BTreeMap<Uuid, Decimal> = BTreeMap::new();
+    for leg in req.legs.iter().filter(|l| l.entry_type == EntryType::Debit) {
+        *debits.entry(leg.account_id).or_default() += leg.amount;
+    }
+    for (account_id, amount) in debits {
+        ensure_within_limits(tx, account_id, amount, now, None).await?;
+    }
+
+    let transaction: Transaction = sqlx::query_as(
         r#"
-        INSERT INTO ledger_entries (id, transaction_id, account_id, entry_type, amount, balance_after, created_at)
-        VALUES ($1, $2, $3, 'debit', $4, $5, $6)
+        INSERT INTO transactions (id, business_id, idempotency_key, type, status, amount, currency, description, metadata, created_at, completed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+        RETURNING *
         "#,
     )
-    .bind(Uuid::new_v4())
     .bind(txn_id)
-    .bind(source_id)
+    .bind(business_id)
+    .bind(idempotency_key)
+    .bind(TransactionType::Journal)
+    .bind(TransactionStatus::Completed)
     .bind(req.amount)
-    .bind(source_new_balance)
+    .bind(&req.currency)
+    .bind(&req.description)
+    .bind(&req.metadata)
     .bind(now)
-    .execute(&mut *tx)
+    .fetch_one(&mut **tx)
     .await?;
 
-    sqlx::query(
+    // Increases first so an account on both sides is never short in between
+    let mut legs: Vec<_> = req.legs.iter().collect();
+    legs.sort_by_key(|l| {
+        accounts
+            .get(&l.account_id)
+            .is_some_and(|a| a.balance_delta(l.entry_type, l.amount) < Decimal::ZERO)
+    });
+
+    for leg in legs {
+        let account = accounts
+            .get_mut(&leg.account_id)
+            .ok_or(AppError::AccountNotFound(leg.account_id))?;
+
+        let currency = leg.currency.as_deref().unwrap_or(&req.currency);
+        if account.currency != currency {
+            return Err(AppError::CurrencyMismatch {
+                from_currency: currency.to_string(),
+                to_currency: account.currency.clone(),
+            });
+        }
+
+        ensure_can_post(account, leg.entry_type)?;
+        ensure_funds(account, leg.entry_type, leg.amount)?;
+        let delta = account.balance_delta(leg.entry_type, leg.amount);
+        account.balance += delta;
+        account.available_balance += delta;
+
+        ledger::post_entry(
+            tx,
+            txn_id,
+            account.id,
+            leg.entry_type,
+            leg.amount,
+            account.balance,
+            now,
+        )
+        .await?;
+    }
+
+    for account in accounts.values() {
+        sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
+            .bind(account.balance)
+            .bind(account.available_balance)
+            .bind(now)
+            .bind(account.id)
+            .execute(&mut **tx)
+            .await?;
+
+        notify_overdraft(
+            tx,
+            account,
+            previous_balances[&account.id],
+            account.balance,
+            txn_id,
+        )
+        .await?;
+    }
+
+    enqueue_webhook(tx, business_id, "transaction.completed", &transaction).await?;
+
+    Ok(transaction)
+}
+
+async fn execute_reversal(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    business_id: Uuid,
+    original_id: Uuid,
+    req: &ReverseTransactionRequest,
+    idempotency_key: Option<&str>,
+) -> Result<Transaction> {
+    let now = Utc::now();
+    let txn_id = Uuid::new_v4();
+
+    // Locking the original serializes concurrent refunds against it
+    let original: Transaction = sqlx::query_as(
         r#"
-        INSERT INTO ledger_entries (id, transaction_id, account_id, entry_type, amount, balance_after, created_at)
-        VALUES ($1, $2, $3, 'credit', $4, $5, $6)
+        SELECT * FROM transactions
+        WHERE id = $1 AND business_id = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(original_id)
+    .bind(business_id)
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or(AppError::TransactionNotFound(original_id))?;
+
+    if original.tx_type == TransactionType::Reversal {
+        return Err(AppError::Validation(
+            "reversal transactions cannot be reversed".into(),
+        ));
+    }
+
+    if original.status != TransactionStatus::Completed {
+        return Err(AppError::Validation(
+            "only completed transactions can be reversed".into(),
+        ));
+    }
+
+    let refunded: Decimal = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(amount), 0) FROM transactions
+        WHERE reversed_transaction_id = $1 AND status = 'completed'
+        "#,
+    )
+    .bind(original_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    let remaining = original.amount - refunded;
+    let amount = req.amount.unwrap_or(remaining);
+
+    if amount <= Decimal::ZERO {
+        return Err(AppError::Validation(if remaining <= Decimal::ZERO {
+            "transaction has already been fully reversed".into()
+        } else {
+            "amount must be positive".into()
+        }));
+    }
+
+    if amount > remaining {
+        return Err(AppError::Validation(format!(
+            "amount exceeds refundable remainder {}",
+            remaining
+        )));
+    }
+
+    // Journal legs carry their own amounts, which a partial refund cannot split exactly
+    if original.tx_type == TransactionType::Journal && amount != original.amount {
+        return Err(AppError::Validation(
+            "journal transactions can only be reversed in full".into(),
+        ));
+    }
+
+    let mut entries: Vec<LedgerEntry> =
+        sqlx::query_as("SELECT * FROM ledger_entries WHERE transaction_id = $1")
+            .bind(original_id)
+            .fetch_all(&mut **tx)
+            .await?;
+
+    let entry_ids: Vec<Uuid> = entries.iter().map(|e| e.id).collect();
+    let leg_refunds: HashMap<Uuid, Decimal> = sqlx::query_as(
+        r#"
+        SELECT le.reversed_entry_id, SUM(le.amount)
+        FROM ledger_entries le
+        JOIN transactions t ON t.id = le.transaction_id
+        WHERE le.reversed_entry_id = ANY($1) AND t.status = 'completed'
+        GROUP BY le.reversed_entry_id
+        "#,
+    )
+    .bind(&entry_ids)
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .collect();
+
+    let account_ids: Vec<Uuid> = entries.iter().map(|e| e.account_id).collect();
+    let mut accounts = ledger::lock_accounts(tx, &account_ids).await?;
+    for account in accounts.values() {
+        ensure_owned(account, business_id)?;
+    }
+
+    // Post the compensating increases before the decreases so an account on
+    // both sides is never short in between
+    entries.sort_by_key(|e| {
+        accounts
+            .get(&e.account_id)
+            .is_some_and(|a| a.balance_delta(e.entry_type, e.amount) > Decimal::ZERO)
+    });
+    let previous_balances: HashMap<Uuid, Decimal> =
+        accounts.iter().map(|(id, a)| (*id, a.balance)).collect();
+
+    let transaction: Transaction = sqlx::query_as(
+        r#"
+        INSERT INTO transactions (id, business_id, idempotency_key, type, status, source_account_id, destination_account_id, amount, currency, description, metadata, created_at, completed_at, reversed_transaction_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $12, $13)
+        RETURNING *
         "#,
     )
-    .bind(Uuid::new_v4())
     .bind(txn_id)
-    .bind(dest_id)
-    .bind(req.amount)
-    .bind(dest_new_balance)
+    .bind(business_id)
+    .bind(idempotency_key)
+    .bind(TransactionType::Reversal)
+    .bind(TransactionStatus::Completed)
+    .bind(original.destination_account_id)
+    .bind(original.source_account_id)
+    .bind(amount)
+    .bind(&original.currency)
+    .bind(&req.description)
+    .bind(&req.metadata)
     .bind(now)
-    .execute(&mut *tx)
+    .bind(original_id)
+    .fetch_one(&mut **tx)
     .await?;
 
-    enqueue_webhook(
-        &mut tx,
-        source.business_id,
-        "transaction.completed",
-        &transaction,
+    // Every original leg is posted back on the opposite side
+    for entry in &entries {
+        let account = accounts
+            .get_mut(&entry.account_id)
+            .ok_or(AppError::AccountNotFound(entry.account_id))?;
+
+        // Each leg is refunded up to its pro rata share of everything refunded
+        // so far, rounded once at ledger scale, so rounding never accumulates
+        // across partial refunds. The final refund takes the exact remainder.
+        let leg_refunded = leg_refunds.get(&entry.id).copied().unwrap_or_default();
+        let leg_remaining = entry.amount - leg_refunded;
+        let leg_amount = if amount == remaining {
+            leg_remaining
+        } else {
+            let target = (entry.amount * (refunded + amount) / original.amount)
+                .round_dp_with_strategy(
+                    ledger::LEDGER_SCALE,
+                    RoundingStrategy::MidpointNearestEven,
+                );
+            (target - leg_refunded).clamp(Decimal::ZERO, leg_remaining)
+        };
+        if leg_amount <= Decimal::ZERO {
+            continue;
+        }
+        let entry_type = entry.entry_type.opposite();
+
+        ensure_can_post(account, entry_type)?;
+        ensure_funds(account, entry_type, leg_amount)?;
+        let delta = account.balance_delta(entry_type, leg_amount);
+        account.balance += delta;
+        account.available_balance += delta;
+
+        let reversal_entry = ledger::post_entry(
+            tx,
+            txn_id,
+            account.id,
+            entry_type,
+            leg_amount,
+            account.balance,
+            now,
+        )
+        .await?;
+
+        sqlx::query("UPDATE ledger_entries SET reversed_entry_id = $1 WHERE id = $2")
+            .bind(entry.id)
+            .bind(reversal_entry.id)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    for account in accounts.values() {
+        sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
+            .bind(account.balance)
+            .bind(account.available_balance)
+            .bind(now)
+            .bind(account.id)
+            .execute(&mut **tx)
+            .await?;
+
+        notify_overdraft(
+            tx,
+            account,
+            previous_balances[&account.id],
+            account.balance,
+            txn_id,
+        )
+        .await?;
+    }
+
+    enqueue_webhook(tx, business_id, "transaction.reversed", &transaction).await?;
+
+    Ok(transaction)
+}
+
+/// The business's fee rule for transactions of `tx_type` in `currency`.
+async fn find_fee_rule(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    business_id: Uuid,
+    tx_type: TransactionType,
+    currency: &str,
+) -> Result<Option<FeeRule>> {
+    let rule: Option<FeeRule> = sqlx::query_as(
+        "SELECT * FROM fee_rules WHERE business_id = $1 AND transaction_type = $2 AND currency = $3",
     )
+    .bind(business_id)
+    .bind(tx_type)
+    .bind(currency)
+    .fetch_optional(&mut **tx)
     .await?;
-    if source.business_id != dest.business_id {
-        enqueue_webhook(
-            &mut tx,
-            dest.business_id,
-            "transaction.completed",
-            &transaction,
+    Ok(rule)
+}
+
+fn fee_amount(fee: &Option<TransactionFee>) -> Decimal {
+    fee.as_ref().map_or(Decimal::ZERO, |f| f.amount)
+}
+
+/// Posts `fee` as a debit on its payer and a credit on its revenue account
+/// and records it on the transaction. Runs after the transaction's own
+/// entries, with both accounts already locked by the caller.
+async fn charge_fee(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    transaction: Transaction,
+    fee: Option<TransactionFee>,
+    now: DateTime<Utc>,
+) -> Result<Transaction> {
+    let Some(fee) = fee else {
+        return Ok(transaction);
+    };
+    if fee.amount <= Decimal::ZERO || fee.payer_account_id == fee.revenue_account_id {
+        return Ok(transaction);
+    }
+
+    // Re-read so the balances include the transaction's own entries
+    let mut accounts =
+        ledger::lock_accounts(tx, &[fee.payer_account_id, fee.revenue_account_id]).await?;
+    let payer = accounts
+        .remove(&fee.payer_account_id)
+        .ok_or(AppError::AccountNotFound(fee.payer_account_id))?;
+    let revenue = accounts
+        .remove(&fee.revenue_account_id)
+        .ok_or(AppError::AccountNotFound(fee.revenue_account_id))?;
+
+    ensure_can_post(&payer, EntryType::Debit)?;
+    ensure_can_post(&revenue, EntryType::Credit)?;
+    ensure_funds(&payer, EntryType::Debit, fee.amount)?;
+
+    for (account, entry_type) in [(&payer, EntryType::Debit), (&revenue, EntryType::Credit)] {
+        let delta = account.balance_delta(entry_type, fee.amount);
+
+        sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
+            .bind(account.balance + delta)
+            .bind(account.available_balance + delta)
+            .bind(now)
+            .bind(account.id)
+            .execute(&mut **tx)
+            .await?;
+
+        ledger::post_entry(
+            tx,
+            transaction.id,
+            account.id,
+            entry_type,
+            fee.amount,
+            account.balance + delta,
+            now,
         )
         .await?;
     }
 
-    tx.commit().await?;
+    let payer_balance = payer.balance + payer.balance_delta(EntryType::Debit, fee.amount);
+    notify_overdraft(tx, &payer, payer.balance, payer_balance, transaction.id).await?;
+
+    let transaction: Transaction =
+        sqlx::query_as("UPDATE transactions SET fees = $1 WHERE id = $2 RETURNING *")
+            .bind(sqlx::types::Json(vec![fee]))
+            .bind(transaction.id)
+            .fetch_one(&mut **tx)
+            .await?;
+
     Ok(transaction)
 }
 
-async fn enqueue_webhook(
+/// Reports another business's account as not found.
+pub(crate) fn ensure_owned(account: &Account, business_id: Uuid) -> Result<()> {
+    if account.business_id != business_id {
+        return Err(AppError::AccountNotFound(account.id));
+    }
+    Ok(())
+}
+
+/// Rejects an entry the account's status does not allow.
+pub(crate) fn ensure_can_post(account: &Account, entry_type: EntryType) -> Result<()> {
+    if !account.can_post(entry_type) {
+        return Err(AppError::AccountNotActive {
+            account_id: account.id,
+            status: account.status.as_str().into(),
+        });
+    }
+    Ok(())
+}
+
+/// Rejects an entry that would take the account past its overdraft limit.
+/// Entries on the account's normal balance side always fit, and settlement
+/// accounts are unbounded.
+pub(crate) fn ensure_funds(
+    account: &Account,
+    entry_type: EntryType,
+    amount: Decimal,
+) -> Result<()> {
+    if account.is_settlement || entry_type == account.account_type.normal_balance() {
+        return Ok(());
+    }
+    if account.spendable() < amount {
+        return Err(AppError::InsufficientFunds {
+            account_id: account.id,
+            available: account.available_balance,
+            overdraft_limit: account.overdraft_limit,
+            requested: amount,
+        });
+    }
+    Ok(())
+}
+
+/// Rejects a debit that would break one of the account's velocity limits.
+/// Must run after the account is locked so concurrent debits are counted.
+/// `amount` is everything the transaction debits from the account, fees
+/// included, since the windows count fee debits too.
+///
+/// The windows count the account's debits from debits, transfers, journals
+/// and captured holds, the fees charged on any transaction, and holds still
+/// reserving funds, except `hold_id` when that hold is being captured.
+/// Reversals and opening balances neither count nor are limited: they undo
+/// or record money rather than move it.
+pub(crate) async fn ensure_within_limits(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    account_id: Uuid,
+    amount: Decimal,
+    now: DateTime<Utc>,
+    hold_id: Option<Uuid>,
+) -> Result<()> {
+    let limits: Option<AccountLimits> =
+        sqlx::query_as("SELECT * FROM account_limits WHERE account_id = $1")
+            .bind(account_id)
+            .fetch_optional(&mut **tx)
+            .await?;
+    let Some(limits) = limits else {
+        return Ok(());
+    };
+
+    let exceeded = |limit, max, attempted| AppError::LimitExceeded {
+        account_id,
+        limit,
+        max,
+        attempted,
+    };
+
+    if let Some(max) = limits.max_transaction_amount {
+        if amount > max {
+            return Err(exceeded("max_transaction_amount", max, amount));
+        }
+    }
+
+    let day_start = now.date_naive().and_time(NaiveTime::MIN).and_utc();
+    let month_start = now
+        .date_naive()
+        .with_day(1)
+        .expect("first of month is valid")
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+
+    let (daily_count, daily_amount, monthly_count, monthly_amount): (i64, Decimal, i64, Decimal) =
+        sqlx::query_as(
+            r#"
+            WITH debits AS (
+                SELECT le.transaction_id, le.created_at, le.amount
+                FROM ledger_entries le
+                JOIN transactions t ON t.id = le.transaction_id
+                WHERE le.account_id = $1 AND le.entry_type = 'debit' AND le.created_at >= $3
+                AND t.type IN ('debit', 'transfer', 'journal', 'credit')
+                UNION ALL
+                SELECT transaction_id, created_at, amount
+                FROM holds
+                WHERE account_id = $1 AND status = 'active' AND created_at >= $3
+                AND id IS DISTINCT FROM $4
+            )
+            SELECT
+                COUNT(DISTINCT transaction_id) FILTER (WHERE created_at >= $2),
+                COALESCE(SUM(amount) FILTER (WHERE created_at >= $2), 0),
+                COUNT(DISTINCT transaction_id),
+                COALESCE(SUM(amount), 0)
+            FROM debits
+            "#,
+        )
+        .bind(account_id)
+        .bind(day_start)
+        .bind(month_start)
+        .bind(hold_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    let checks = [
+        (
+            "daily_debit_count",
+            limits.daily_debit_count.map(Decimal::from),
+            Decimal::from(daily_count + 1),
+        ),
+        (
+            "daily_debit_amount",
+            limits.daily_debit_amount,
+            daily_amount + amount,
+        ),
+        (
+            "monthly_debit_count",
+            limits.monthly_debit_count.map(Decimal::from),
+            Decimal::from(monthly_count + 1),
+        ),
+        (
+            "monthly_debit_amount",
+            limits.monthly_debit_amount,
+            monthly_amount + amount,
+        ),
+    ];
+
+    for (limit, max, attempted) in checks {
+        if let Some(max) = max {
+            if attempted > max {
+                return Err(exceeded(limit, max, attempted));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Enqueues `account.overdrawn` when a posting takes the account's balance
+/// from non-negative to negative.
+pub(crate) async fn notify_overdraft(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    account: &Account,
+    previous_balance: Decimal,
+    balance: Decimal,
+    transaction_id: Uuid,
+) -> Result<()> {
+    if account.is_settlement || previous_balance < Decimal::ZERO || balance >= Decimal::ZERO {
+        return Ok(());
+    }
+
+    let event = AccountOverdrawnEvent {
+        account_id: account.id,
+        transaction_id,
+        balance,
+        overdraft_limit: account.overdraft_limit,
+    };
+    enqueue_webhook(tx, account.business_id, "account.overdrawn", &event).await
+}
+
+pub(crate) async fn enqueue_webhook<T: Serialize>(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     business_id: Uuid,
     event_type: &str,
-    transaction: &Transaction,
+    data: &T,
 ) -> Result<()> {
-    let payload = WebhookPayload::new(event_type, serde_json::to_value(transaction)?);
+    let payload = WebhookPayload::new(event_type, serde_json::to_value(data)?);
 
     sqlx::query(
         r#"
@@ -1,13 +1,14 @@
 use std::sync::Arc;
 
 use axum::body::Body;
-use axum::extract::State;
-use axum::http::{header, Request};
+use axum::extract::{OriginalUri, State};
+use axum::http::{header, HeaderMap, Request};
 use axum::middleware::Next;
 use axum::response::Response;
 use chrono::Utc;
+use uuid::Uuid;
 
-use crate::domain::ApiKey;
+use crate::domain::{request_signing_string, verify_signature, ApiKey, Scope};
 use crate::error::AppError;
 use crate::AppState;
 
@@ -16,13 +17,37 @@ pub struct AuthContext {
     pub api_key: ApiKey,
 }
 
+pub const KEY_ID_HEADER: &str = "x-payx-key-id";
+pub const TIMESTAMP_HEADER: &str = "x-payx-timestamp";
+pub const NONCE_HEADER: &str = "x-payx-nonce";
+pub const SIGNATURE_HEADER: &str = "x-payx-signature";
+
+/// Largest body a signed request may carry, matching axum's default limit.
+const MAX_SIGNED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Authenticates the request with either a bearer key or, when it carries
+/// `X-Payx-Signature`, a request signature.
 pub async fn middleware(
     State(state): State<Arc<AppState>>,
-    mut req: Request<Body>,
+    req: Request<Body>,
     next: Next,
 ) -> Result<Response, AppError> {
-    let auth_header = req
-        .headers()
+    let (api_key, mut req) = if req.headers().contains_key(SIGNATURE_HEADER) {
+        authenticate_signed(&state, req).await?
+    } else {
+        (authenticate_bearer(&state, req.headers()).await?, req)
+    };
+
+    // Written back in batches by `workers::api_key_sync`.
+    state.api_keys.touch(api_key.id, Utc::now());
+
+    req.extensions_mut().insert(AuthContext { api_key });
+
+    Ok(next.run(req).await)
+}
+
+async fn authenticate_bearer(state: &AppState, headers: &HeaderMap) -> Result<ApiKey, AppError> {
+    let auth_header = headers
         .get(header::AUTHORIZATION)
         .and_then(|h| h.to_str().ok())
         .ok_or(AppError::InvalidApiKey)?;
@@ -34,11 +59,15 @@ pub async fn middleware(
     if key.len() < 12 {
         return Err(AppError::InvalidApiKey);
     }
-    let prefix = &key[..12];
 
+    if let Some(api_key) = state.api_keys.get(key) {
+        return Ok(api_key);
+    }
+
+    let generation = state.api_keys.generation();
     let api_key: ApiKey =
         sqlx::query_as("SELECT * FROM api_keys WHERE key_prefix = $1 AND revoked_at IS NULL")
-            .bind(prefix)
+            .bind(&key[..12])
             .fetch_optional(&state.db)
             .await?
             .ok_or(AppError::InvalidApiKey)?;
@@ -47,13 +76,120 @@ pub async fn middleware(
         return Err(AppError::InvalidApiKey);
     }
 
-    sqlx::query("UPDATE api_keys SET last_used_at = $1 WHERE id = $2")
-        .bind(Utc::now())
-        .bind(api_key.id)
-        .execute(&state.db)
-        .await?;
+    state.api_keys.insert(key, api_key.clone(), generation);
+    Ok(api_key)
+}
 
-    req.extensions_mut().insert(AuthContext { api_key });
+/// Checks the HMAC of a signed request against the key's signing secret and
+/// records its nonce. The body is buffered to hash it and handed on intact.
+async fn authenticate_signed(
+    state: &AppState,
+    req: Request<Body>,
+) -> Result<(ApiKey, Request<Body>), AppError> {
+    // Scoped so the borrow of `req` does not live across an await.
+    let (key_id, timestamp, nonce, signature) = {
+        let header_value = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|h| h.to_str().ok())
+                .ok_or_else(|| AppError::InvalidSignature(format!("missing {} header", name)))
+        };
+
+        let key_id: Uuid = header_value(KEY_ID_HEADER)?.parse().map_err(|_| {
+            AppError::InvalidSignature(format!("malformed {} header", KEY_ID_HEADER))
+        })?;
+        let timestamp: i64 = header_value(TIMESTAMP_HEADER)?.parse().map_err(|_| {
+            AppError::InvalidSignature(format!("malformed {} header", TIMESTAMP_HEADER))
+        })?;
+        let nonce = header_value(NONCE_HEADER)?.to_string();
+        let signature = header_value(SIGNATURE_HEADER)?.to_string();
+        (key_id, timestamp, nonce, signature)
+    };
+
+    if Utc::now().timestamp().abs_diff(timestamp) > state.config.request_signature_max_skew_seconds
+    {
+        return Err(AppError::InvalidSignature(
+            "timestamp is outside the allowed skew".into(),
+        ));
+    }
+    if nonce.is_empty() || nonce.len() > 128 {
+        return Err(AppError::InvalidSignature(
+            "nonce must be 1 to 128 characters".into(),
+        ));
+    }
+
+    let api_key: ApiKey =
+        sqlx::query_as("SELECT * FROM api_keys WHERE id = $1 AND revoked_at IS NULL")
+            .bind(key_id)
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or(AppError::InvalidApiKey)?;
+
+    if !api_key.is_valid() {
+        return Err(AppError::InvalidApiKey);
+    }
+    let secret = api_key
+        .open_signing_secret(&state.config.secret_encryption_key)?
+        .ok_or_else(|| {
+            AppError::InvalidSignature("api key has no signing secret; rotate it to get one".into())
+        })?;
+
+    // Nested routers see the path without their prefix; the client signed
+    // the full one.
+    let path_and_query = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| &uri.0)
+        .unwrap_or(req.uri())
+        .path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_default();
+
+    let (parts, body) = req.into_parts();
+    let body = axum::body::to_bytes(body, MAX_SIGNED_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::Validation("request body too large".into()))?;
+
+    let signing_string = request_signing_string(
+        parts.method.as_str(),
+        &path_and_query,
+        timestamp,
+        &nonce,
+        &body,
+    );
+    if !verify_signature(signing_string.as_bytes(), &secret, &signature) {
+        return Err(AppError::InvalidSignature("signature mismatch".into()));
+    }
+
+    let recorded = sqlx::query(
+        "INSERT INTO request_nonces (api_key_id, nonce) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+    )
+    .bind(api_key.id)
+    .bind(&nonce)
+    .execute(&state.db)
+    .await?;
+    if recorded.rows_affected() == 0 {
+        return Err(AppError::InvalidSignature("nonce already used".into()));
+    }
+
+    Ok((api_key, Request::from_parts(parts, Body::from(body))))
+}
+
+/// Rejects the request unless the authenticated key holds `scope`. Layered
+/// onto individual routes in `routes::build`, inside [`middleware`].
+pub async fn require_scope(
+    State(scope): State<Scope>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let auth = req
+        .extensions()
+        .get::<AuthContext>()
+        .expect("auth middleware must run first");
+
+    if !auth.api_key.has_scope(scope) {
+        return Err(AppError::InsufficientScope(scope));
+    }
 
     Ok(next.run(req).await)
 }
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::domain::{constant_time_eq, ApiKey};
+
+/// Postgres notification channel carrying the ID of a changed API key.
+pub const API_KEYS_CHANNEL: &str = "api_keys_changed";
+
+/// API keys that passed the Argon2 verify recently, keyed by prefix, so that
+/// repeat requests skip the database lookup and the hash.
+///
+/// Entries live for at most the TTL. Revocations and other changes made on
+/// any instance are announced on [`API_KEYS_CHANNEL`] and evict the key
+/// straight away; see `workers::api_key_sync`.
+///
+/// The cache also collects `last_used_at` times, which are written back in
+/// batches instead of once per request.
+pub struct ApiKeyCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<String, CachedKey>>,
+    /// Bumped on every eviction, so a lookup that raced one is not cached.
+    generation: AtomicU64,
+    last_used: Mutex<HashMap<Uuid, DateTime<Utc>>>,
+}
+
+struct CachedKey {
+    api_key: ApiKey,
+    /// SHA-256 of the full key that was verified. A request presenting a
+    /// different key with the same prefix misses the cache.
+    digest: [u8; 32],
+    cached_at: Instant,
+}
+
+impl ApiKeyCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+            last_used: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The key `key` authenticates as, if it was verified within the TTL and
+    /// has not expired since.
+    pub fn get(&self, key: &str) -> Option<ApiKey> {
+        let entries = self.entries.read().expect("api key cache poisoned");
+        let cached = entries.get(&key[..12])?;
+
+        if cached.cached_at.elapsed() >= self.ttl
+            || !constant_time_eq(&cached.digest, &digest(key))
+            || !cached.api_key.is_valid()
+        {
+            return None;
+        }
+
+        Some(cached.api_key.clone())
+    }
+
+    /// Read before looking a key up in the database, and passed to
+    /// [`insert`](Self::insert) afterwards.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Remembers that `key` verified as `api_key`, unless something was
+    /// evicted since `generation` was read.
+    pub fn insert(&self, key: &str, api_key: ApiKey, generation: u64) {
+        if self.ttl.is_zero() {
+            return;
+        }
+
+        let cached = CachedKey {
+            api_key,
+            digest: digest(key),
+            cached_at: Instant::now(),
+        };
+
+        let mut entries = self.entries.write().expect("api key cache poisoned");
+        if self.generation() == generation {
+            entries.insert(key[..12].to_string(), cached);
+        }
+    }
+
+    pub fn invalidate(&self, id: Uuid) {
+        let mut entries = self.entries.write().expect("api key cache poisoned");
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        entries.retain(|_, cached| cached.api_key.id != id);
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.write().expect("api key cache poisoned");
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        entries.clear();
+    }
+
+    pub fn evict_expired(&self) {
+        let ttl = self.ttl;
+        let mut entries = self.entries.write().expect("api key cache poisoned");
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        entries.retain(|_, cached| cached.cached_at.elapsed() < ttl);
+    }
+
+    /// Records a use of the key at `at`, keeping the latest time.
+    pub fn touch(&self, id: Uuid, at: DateTime<Utc>) {
+        let mut last_used = self.last_used.lock().expect("api key cache poisoned");
+        let entry = last_used.entry(id).or_insert(at);
+        *entry = (*entry).max(at);
+    }
+
+    /// A use of the key recorded here but not written to the database yet.
+    pub fn pending_last_used(&self, id: Uuid) -> Option<DateTime<Utc>> {
+        self.last_used
+            .lock()
+            .expect("api key cache poisoned")
+            .get(&id)
+            .copied()
+    }
+
+    /// Takes every use recorded since the last call.
+    pub fn take_last_used(&self) -> HashMap<Uuid, DateTime<Utc>> {
+        std::mem::take(&mut *self.last_used.lock().expect("api key cache poisoned"))
+    }
+}
+
+fn digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
@@ -1,2 +1,3 @@
 pub mod auth;
+pub mod key_cache;
 pub mod rate_limit;
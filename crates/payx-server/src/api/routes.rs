@@ -4,7 +4,7 @@ use std::time::Duration;
 use axum::http::StatusCode;
 use axum::middleware::from_fn_with_state;
 use axum::response::IntoResponse;
-use axum::routing::{delete, get, post, put};
+use axum::routing::{delete, get, patch, post, put, MethodRouter};
 use axum::{Json, Router};
 use serde_json::json;
 use tower::ServiceBuilder;
@@ -14,8 +14,12 @@ use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetReques
 use tower_http::timeout::TimeoutLayer;
 use tower_http::trace::TraceLayer;
 
-use crate::api::handlers::{accounts, businesses, health, transactions, webhooks};
+use crate::api::handlers::{
+    accounts, api_keys, businesses, fees, health, holds, integrity, interest, transactions,
+    webhooks,
+};
 use crate::api::middleware::{auth, rate_limit};
+use crate::domain::Scope;
 use crate::AppState;
 
 async fn fallback() -> impl IntoResponse {
@@ -30,29 +34,187 @@ async fn fallback() -> impl IntoResponse {
     )
 }
 
+/// Only lets keys holding `scope` reach `route`.
+fn scoped(scope: Scope, route: MethodRouter<Arc<AppState>>) -> MethodRouter<Arc<AppState>> {
+    route.layer(from_fn_with_state(scope, auth::require_scope))
+}
+
 pub fn build(state: Arc<AppState>) -> Router {
     let protected = Router::new()
-        .route("/businesses", get(businesses::list))
-        .route("/businesses/:id", get(businesses::get))
-        .route("/businesses/:id", put(businesses::update))
-        .route("/accounts", get(accounts::list))
-        .route("/accounts", post(accounts::create))
-        .route("/accounts/:id", get(accounts::get))
+        .route(
+            "/businesses",
+            scoped(Scope::BusinessesRead, get(businesses::list)),
+        )
+        .route(
+            "/businesses/:id",
+            scoped(Scope::BusinessesRead, get(businesses::get)),
+        )
+        .route(
+            "/businesses/:id",
+            scoped(Scope::BusinessesWrite, put(businesses::update)),
+        )
+        .route(
+            "/api_keys",
+            scoped(Scope::ApiKeysManage, get(api_keys::list)),
+        )
+        .route(
+            "/api_keys",
+            scoped(Scope::ApiKeysManage, post(api_keys::create)),
+        )
+        .route(
+            "/api_keys/:id/rotate",
+            scoped(Scope::ApiKeysManage, post(api_keys::rotate)),
+        )
+        .route(
+            "/api_keys/:id/revoke",
+            scoped(Scope::ApiKeysManage, post(api_keys::revoke)),
+        )
+        .route(
+            "/accounts",
+            scoped(Scope::AccountsRead, get(accounts::list)),
+        )
+        .route(
+            "/accounts",
+            scoped(Scope::AccountsWrite, post(accounts::create)),
+        )
+        .route(
+            "/accounts/:id",
+            scoped(Scope::AccountsRead, get(accounts::get)),
+        )
+        .route(
+            "/accounts/:id",
+            scoped(Scope::AccountsWrite, patch(accounts::update)),
+        )
+        .route(
+            "/accounts/:id/freeze",
+            scoped(Scope::AccountsWrite, post(accounts::freeze)),
+        )
+        .route(
+            "/accounts/:id/unfreeze",
+            scoped(Scope::AccountsWrite, post(accounts::unfreeze)),
+        )
+        .route(
+            "/accounts/:id/close",
+            scoped(Scope::AccountsWrite, post(accounts::close)),
+        )
         .route(
             "/accounts/:id/transactions",
-            get(accounts::list_transactions),
+            scoped(Scope::AccountsRead, get(accounts::list_transactions)),
+        )
+        .route(
+            "/accounts/:id/ledger",
+            scoped(Scope::AccountsRead, get(accounts::list_ledger_entries)),
+        )
+        .route(
+            "/accounts/:id/ledger/verify",
+            scoped(Scope::AccountsRead, get(accounts::verify_ledger)),
+        )
+        .route(
+            "/accounts/:id/balance",
+            scoped(Scope::AccountsRead, get(accounts::get_balance)),
+        )
+        .route(
+            "/accounts/:id/balances/daily",
+            scoped(Scope::AccountsRead, get(accounts::get_daily_balances)),
+        )
+        .route(
+            "/accounts/:id/statement",
+            scoped(Scope::AccountsRead, get(accounts::get_statement)),
+        )
+        .route(
+            "/accounts/:id/rollup",
+            scoped(Scope::AccountsRead, get(accounts::get_rollup)),
+        )
+        .route(
+            "/accounts/:id/limits",
+            scoped(Scope::AccountsRead, get(accounts::get_limits)),
+        )
+        .route(
+            "/accounts/:id/limits",
+            scoped(Scope::AccountsWrite, put(accounts::set_limits)),
+        )
+        .route(
+            "/accounts/:id/interest",
+            scoped(Scope::AccountsRead, get(interest::get)),
+        )
+        .route(
+            "/accounts/:id/interest",
+            scoped(Scope::AccountsWrite, put(interest::set)),
+        )
+        .route(
+            "/accounts/:id/interest/accruals",
+            scoped(Scope::AccountsRead, get(interest::list_accruals)),
+        )
+        .route(
+            "/transactions",
+            scoped(Scope::TransactionsRead, get(transactions::list)),
+        )
+        .route(
+            "/transactions",
+            scoped(Scope::TransactionsWrite, post(transactions::create)),
+        )
+        .route(
+            "/transactions/batch",
+            scoped(Scope::TransactionsWrite, post(transactions::batch)),
+        )
+        .route(
+            "/transactions/:id",
+            scoped(Scope::TransactionsRead, get(transactions::get)),
+        )
+        .route(
+            "/transactions/:id/reverse",
+            scoped(Scope::TransactionsWrite, post(transactions::reverse)),
+        )
+        .route("/fee_rules", scoped(Scope::FeesRead, get(fees::list)))
+        .route("/fee_rules", scoped(Scope::FeesWrite, post(fees::create)))
+        .route("/fee_rules/:id", scoped(Scope::FeesRead, get(fees::get)))
+        .route(
+            "/fee_rules/:id",
+            scoped(Scope::FeesWrite, delete(fees::delete)),
+        )
+        .route(
+            "/holds",
+            scoped(Scope::TransactionsWrite, post(holds::authorize)),
+        )
+        .route(
+            "/holds/:id",
+            scoped(Scope::TransactionsRead, get(holds::get)),
+        )
+        .route(
+            "/holds/:id/capture",
+            scoped(Scope::TransactionsWrite, post(holds::capture)),
+        )
+        .route(
+            "/holds/:id/void",
+            scoped(Scope::TransactionsWrite, post(holds::void)),
+        )
+        .route(
+            "/integrity/report",
+            scoped(Scope::IntegrityRead, get(integrity::report)),
+        )
+        .route(
+            "/webhooks/endpoints",
+            scoped(Scope::WebhooksManage, post(webhooks::create_endpoint)),
+        )
+        .route(
+            "/webhooks/endpoints/:id",
+            scoped(Scope::WebhooksManage, put(webhooks::update_endpoint)),
+        )
+        .route(
+            "/webhooks/endpoints/:id",
+            scoped(Scope::WebhooksManage, delete(webhooks::delete_endpoint)),
+        )
+        .route(
+            "/webhooks/deliveries",
+            scoped(Scope::WebhooksRead, get(webhooks::list_deliveries)),
+        )
+        .route(
+            "/webhooks/deliveries/:id",
+            scoped(Scope::WebhooksRead, get(webhooks::get_delivery)),
         )
-        .route("/transactions", get(transactions::list))
-        .route("/transactions", post(transactions::create))
-        .route("/transactions/:id", get(transactions::get))
-        .route("/webhooks/endpoints", post(webhooks::create_endpoint))
-        .route("/webhooks/endpoints/:id", put(webhooks::update_endpoint))
-        .route("/webhooks/endpoints/:id", delete(webhooks::delete_endpoint))
-        .route("/webhooks/deliveries", get(webhooks::list_deliveries))
-        .route("/webhooks/deliveries/:id", get(webhooks::get_delivery))
         .route(
             "/webhooks/deliveries/:id/retry",
-            post(webhooks::retry_delivery),
+            scoped(Scope::WebhooksManage, post(webhooks::retry_delivery)),
         )
         .layer(from_fn_with_state(state.clone(), rate_limit::middleware))
         .layer(from_fn_with_state(state.clone(), auth::middleware));
@@ -8,6 +8,16 @@ pub struct Config {
     pub db_max_connections: u32,
     pub otlp_endpoint: Option<String>,
     pub rate_limit_per_minute: i32,
+    pub hold_ttl_seconds: i64,
+    pub integrity_check_interval_seconds: u64,
+    pub batch_max_items: usize,
+    pub interest_accrual_interval_seconds: u64,
+    pub balance_snapshot_interval_seconds: u64,
+    pub api_key_cache_ttl_seconds: u64,
+    pub api_key_last_used_flush_seconds: u64,
+    pub request_signature_max_skew_seconds: u64,
+    pub ledger_hash_key: String,
+    pub secret_encryption_key: [u8; 32],
 }
 
 impl Config {
@@ -22,6 +32,51 @@ impl Config {
             rate_limit_per_minute: env::var("RATE_LIMIT_PER_MINUTE")
                 .unwrap_or_else(|_| "100".into())
                 .parse()?,
+            hold_ttl_seconds: env::var("HOLD_TTL_SECONDS")
+                .unwrap_or_else(|_| "604800".into())
+                .parse()?,
+            integrity_check_interval_seconds: env::var("INTEGRITY_CHECK_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "3600".into())
+                .parse()?,
+            batch_max_items: env::var("BATCH_MAX_ITEMS")
+                .unwrap_or_else(|_| "1000".into())
+                .parse()?,
+            interest_accrual_interval_seconds: env::var("INTEREST_ACCRUAL_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "3600".into())
+                .parse()?,
+            balance_snapshot_interval_seconds: env::var("BALANCE_SNAPSHOT_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "3600".into())
+                .parse()?,
+            api_key_cache_ttl_seconds: env::var("API_KEY_CACHE_TTL_SECONDS")
+                .unwrap_or_else(|_| "60".into())
+                .parse()?,
+            api_key_last_used_flush_seconds: env::var("API_KEY_LAST_USED_FLUSH_SECONDS")
+                .unwrap_or_else(|_| "10".into())
+                .parse()?,
+            request_signature_max_skew_seconds: env::var("REQUEST_SIGNATURE_MAX_SKEW_SECONDS")
+                .unwrap_or_else(|_| "300".into())
+                .parse()?,
+            ledger_hash_key: ledger_hash_key()?,
+            secret_encryption_key: secret_encryption_key()?,
         })
     }
 }
+
+/// Ledger hashes are only as strong as this key, so a short one is refused.
+fn ledger_hash_key() -> Result<String> {
+    let key = env::var("LEDGER_HASH_KEY").context("LEDGER_HASH_KEY required")?;
+    if key.len() < 32 {
+        anyhow::bail!("LEDGER_HASH_KEY must be at least 32 characters");
+    }
+    Ok(key)
+}
+
+/// The AES-256 key that encrypts API key signing secrets at rest, as 64 hex
+/// characters.
+fn secret_encryption_key() -> Result<[u8; 32]> {
+    let key = env::var("SECRET_ENCRYPTION_KEY").context("SECRET_ENCRYPTION_KEY required")?;
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(key.trim(), &mut bytes)
+        .context("SECRET_ENCRYPTION_KEY must be 64 hex characters")?;
+    Ok(bytes)
+}
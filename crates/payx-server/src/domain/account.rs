@@ -1,51 +1,212 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
 
+use super::EntryType;
+
+/// Chart-of-accounts classification. The type fixes the account's normal
+/// balance side: entries on that side increase the balance, entries on the
+/// other side decrease it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AccountType {
+    Asset,
+    #[default]
+    Liability,
+    Equity,
+    Revenue,
+    Expense,
+}
+
+impl AccountType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Asset => "asset",
+            Self::Liability => "liability",
+            Self::Equity => "equity",
+            Self::Revenue => "revenue",
+            Self::Expense => "expense",
+        }
+    }
+
+    pub fn normal_balance(&self) -> EntryType {
+        match self {
+            Self::Asset | Self::Expense => EntryType::Debit,
+            Self::Liability | Self::Equity | Self::Revenue => EntryType::Credit,
+        }
+    }
+
+    /// The change to the balance of an entry of `amount` on the given side.
+    pub fn signed(&self, entry_type: EntryType, amount: Decimal) -> Decimal {
+        if entry_type == self.normal_balance() {
+            amount
+        } else {
+            -amount
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AccountStatus {
+    Active,
+    Frozen,
+    Closed,
+}
+
+impl AccountStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Frozen => "frozen",
+            Self::Closed => "closed",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, sqlx::FromRow)]
 pub struct Account {
     pub id: Uuid,
     pub business_id: Uuid,
-    pub account_type: String,
+    pub account_type: AccountType,
     pub currency: String,
     pub balance: Decimal,
     pub available_balance: Decimal,
     pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub overdraft_limit: Decimal,
+    pub status: AccountStatus,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub external_id: Option<String>,
+    pub name: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub parent_account_id: Option<Uuid>,
+    /// The per-business, per-currency asset account that credits and
+    /// debits post against.
+    pub is_settlement: bool,
+}
+
+impl Account {
+    /// The change to the balance of an entry of `amount` on the given side.
+    pub fn balance_delta(&self, entry_type: EntryType, amount: Decimal) -> Decimal {
+        self.account_type.signed(entry_type, amount)
+    }
+
+    /// The most the balance can decrease before the account exceeds its
+    /// overdraft limit.
+    pub fn spendable(&self) -> Decimal {
+        self.available_balance + self.overdraft_limit
+    }
+
+    /// Whether the account's status allows an entry on the given side.
+    /// Frozen accounts can still receive funds: entries on their normal
+    /// balance side, which only increase the balance.
+    pub fn can_post(&self, entry_type: EntryType) -> bool {
+        match self.status {
+            AccountStatus::Active => true,
+            AccountStatus::Frozen => entry_type == self.account_type.normal_balance(),
+            AccountStatus::Closed => false,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateAccountRequest {
-    pub business_id: Uuid,
-    #[serde(default = "default_account_type")]
-    pub account_type: String,
+    /// Defaults to the API key's business, the only one it may name.
+    pub business_id: Option<Uuid>,
+    #[serde(default, deserialize_with = "legacy_account_type")]
+    pub account_type: AccountType,
     #[serde(default = "default_currency")]
     pub currency: String,
     #[serde(default)]
     pub initial_balance: Decimal,
+    #[serde(default)]
+    pub overdraft_limit: Decimal,
+    pub external_id: Option<String>,
+    pub name: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub parent_account_id: Option<Uuid>,
 }
 
-fn default_account_type() -> String {
-    "checking".into()
+/// Omitted fields keep their current values. An explicit `null` clears
+/// `external_id`, `name` or `metadata`.
+#[derive(Debug, Deserialize)]
+pub struct UpdateAccountRequest {
+    pub overdraft_limit: Option<Decimal>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub external_id: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub metadata: Option<Option<serde_json::Value>>,
 }
 
 fn default_currency() -> String {
     "USD".into()
 }
 
+/// Reads a field that is present, so that `null` becomes `Some(None)`; with
+/// `#[serde(default)]` an omitted field stays `None`.
+fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Account types clients used before accounts were classified. They held
+/// customer funds, and create `liability` accounts as the classification
+/// migration made them.
+const LEGACY_ACCOUNT_TYPES: [&str; 2] = ["checking", "savings"];
+
+/// Reads an account type, accepting [`LEGACY_ACCOUNT_TYPES`] as
+/// `liability`. Anything else unknown, including another case, is refused.
+fn legacy_account_type<'de, D>(deserializer: D) -> Result<AccountType, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    match value.as_str() {
+        "asset" => Ok(AccountType::Asset),
+        "liability" => Ok(AccountType::Liability),
+        "equity" => Ok(AccountType::Equity),
+        "revenue" => Ok(AccountType::Revenue),
+        "expense" => Ok(AccountType::Expense),
+        legacy if LEGACY_ACCOUNT_TYPES.contains(&legacy) => Ok(AccountType::Liability),
+        other => Err(D::Error::custom(format!(
+            "unknown account_type '{}': expected asset, liability, equity, revenue or expense",
+            other
+        ))),
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct AccountResponse {
     pub id: Uuid,
     pub business_id: Uuid,
-    pub account_type: String,
+    pub external_id: Option<String>,
+    pub name: Option<String>,
+    pub parent_account_id: Option<Uuid>,
+    pub account_type: AccountType,
+    pub normal_balance: EntryType,
+    pub is_settlement: bool,
     pub currency: String,
     #[serde(with = "rust_decimal::serde::str")]
     pub balance: Decimal,
     #[serde(with = "rust_decimal::serde::str")]
     pub available_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub overdraft_limit: Decimal,
+    pub status: AccountStatus,
+    pub metadata: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
 }
 
 impl From<Account> for AccountResponse {
@@ -53,11 +214,101 @@ impl From<Account> for AccountResponse {
         Self {
             id: a.id,
             business_id: a.business_id,
+            external_id: a.external_id,
+            name: a.name,
+            parent_account_id: a.parent_account_id,
             account_type: a.account_type,
+            normal_balance: a.account_type.normal_balance(),
+            is_settlement: a.is_settlement,
             currency: a.currency,
             balance: a.balance,
             available_balance: a.available_balance,
+            overdraft_limit: a.overdraft_limit,
+            status: a.status,
+            metadata: a.metadata,
             created_at: a.created_at,
+            closed_at: a.closed_at,
         }
     }
 }
+
+#[derive(Debug, Serialize)]
+pub struct BalanceResponse {
+    pub account_id: Uuid,
+    pub currency: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub available_balance: Decimal,
+    pub as_of: DateTime<Utc>,
+}
+
+/// Payload of the `account.overdrawn` webhook.
+#[derive(Debug, Serialize)]
+pub struct AccountOverdrawnEvent {
+    pub account_id: Uuid,
+    pub transaction_id: Uuid,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub overdraft_limit: Decimal,
+}
+
+/// Consolidated balance of one currency across an account and its
+/// descendants.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct RollupBalance {
+    pub currency: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub available_balance: Decimal,
+    pub accounts: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountRollupResponse {
+    pub account_id: Uuid,
+    /// The side the rolled-up balances are positive on: the account's own
+    /// normal balance.
+    pub normal_balance: EntryType,
+    pub balances: Vec<RollupBalance>,
+}
+
+/// An account's closing balance for one day in its business's timezone.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BalanceSnapshot {
+    pub account_id: Uuid,
+    pub snapshot_date: NaiveDate,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Velocity limits on an account's debits. `None` leaves a limit unenforced.
+#[derive(Debug, Clone, Default, Serialize, sqlx::FromRow)]
+pub struct AccountLimits {
+    pub account_id: Uuid,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub max_transaction_amount: Option<Decimal>,
+    pub daily_debit_count: Option<i32>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub daily_debit_amount: Option<Decimal>,
+    pub monthly_debit_count: Option<i32>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub monthly_debit_amount: Option<Decimal>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Replaces every limit on the account; omitted limits are removed.
+#[derive(Debug, Deserialize)]
+pub struct SetAccountLimitsRequest {
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub max_transaction_amount: Option<Decimal>,
+    pub daily_debit_count: Option<i32>,
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub daily_debit_amount: Option<Decimal>,
+    pub monthly_debit_count: Option<i32>,
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub monthly_debit_amount: Option<Decimal>,
+}
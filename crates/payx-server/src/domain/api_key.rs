@@ -1,10 +1,102 @@
+use std::fmt;
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use anyhow::Context;
 use argon2::{password_hash::SaltString, Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{DateTime, Utc};
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+/// A permission granted to an API key. Write scopes do not imply the
+/// matching read scope.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, sqlx::Type,
+)]
+#[sqlx(type_name = "varchar")]
+pub enum Scope {
+    #[serde(rename = "businesses:read")]
+    #[sqlx(rename = "businesses:read")]
+    BusinessesRead,
+    #[serde(rename = "businesses:write")]
+    #[sqlx(rename = "businesses:write")]
+    BusinessesWrite,
+    #[serde(rename = "accounts:read")]
+    #[sqlx(rename = "accounts:read")]
+    AccountsRead,
+    #[serde(rename = "accounts:write")]
+    #[sqlx(rename = "accounts:write")]
+    AccountsWrite,
+    #[serde(rename = "transactions:read")]
+    #[sqlx(rename = "transactions:read")]
+    TransactionsRead,
+    /// Moves money: transactions, batches, reversals and holds.
+    #[serde(rename = "transactions:write")]
+    #[sqlx(rename = "transactions:write")]
+    TransactionsWrite,
+    #[serde(rename = "fees:read")]
+    #[sqlx(rename = "fees:read")]
+    FeesRead,
+    #[serde(rename = "fees:write")]
+    #[sqlx(rename = "fees:write")]
+    FeesWrite,
+    #[serde(rename = "webhooks:read")]
+    #[sqlx(rename = "webhooks:read")]
+    WebhooksRead,
+    #[serde(rename = "webhooks:manage")]
+    #[sqlx(rename = "webhooks:manage")]
+    WebhooksManage,
+    #[serde(rename = "integrity:read")]
+    #[sqlx(rename = "integrity:read")]
+    IntegrityRead,
+    #[serde(rename = "api_keys:manage")]
+    #[sqlx(rename = "api_keys:manage")]
+    ApiKeysManage,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 12] = [
+        Scope::BusinessesRead,
+        Scope::BusinessesWrite,
+        Scope::AccountsRead,
+        Scope::AccountsWrite,
+        Scope::TransactionsRead,
+        Scope::TransactionsWrite,
+        Scope::FeesRead,
+        Scope::FeesWrite,
+        Scope::WebhooksRead,
+        Scope::WebhooksManage,
+        Scope::IntegrityRead,
+        Scope::ApiKeysManage,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::BusinessesRead => "businesses:read",
+            Scope::BusinessesWrite => "businesses:write",
+            Scope::AccountsRead => "accounts:read",
+            Scope::AccountsWrite => "accounts:write",
+            Scope::TransactionsRead => "transactions:read",
+            Scope::TransactionsWrite => "transactions:write",
+            Scope::FeesRead => "fees:read",
+            Scope::FeesWrite => "fees:write",
+            Scope::WebhooksRead => "webhooks:read",
+            Scope::WebhooksManage => "webhooks:manage",
+            Scope::IntegrityRead => "integrity:read",
+            Scope::ApiKeysManage => "api_keys:manage",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct ApiKey {
     pub id: Uuid,
@@ -12,22 +104,64 @@ pub struct ApiKey {
     pub key_hash: String,
     pub key_prefix: String,
     pub name: Option<String>,
+    pub scopes: Vec<Scope>,
     pub rate_limit_per_minute: i32,
     pub created_at: DateTime<Utc>,
     pub expires_at: Option<DateTime<Utc>>,
     pub revoked_at: Option<DateTime<Utc>>,
     pub last_used_at: Option<DateTime<Utc>>,
+    /// Encrypted with [`seal_signing_secret`]; see
+    /// [`open_signing_secret`](Self::open_signing_secret).
+    pub signing_secret: Option<String>,
 }
 
+/// A newly issued key. `key` and `signing_secret` are shown only once and
+/// cannot be retrieved later.
 #[derive(Debug, Serialize)]
 pub struct GeneratedApiKey {
     pub id: Uuid,
     pub key: String,
     pub prefix: String,
+    pub signing_secret: String,
+    pub name: Option<String>,
+    pub scopes: Vec<Scope>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// The listable view of a key, without its hash.
+#[derive(Debug, Serialize)]
+pub struct ApiKeySummary {
+    pub id: Uuid,
+    pub prefix: String,
+    pub name: Option<String>,
+    pub scopes: Vec<Scope>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiKey> for ApiKeySummary {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            prefix: key.key_prefix,
+            name: key.name,
+            scopes: key.scopes,
+            created_at: key.created_at,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+        }
+    }
 }
 
 impl ApiKey {
-    pub fn generate(business_id: Uuid) -> (Self, GeneratedApiKey) {
+    pub fn generate(
+        business_id: Uuid,
+        name: Option<String>,
+        scopes: Vec<Scope>,
+        expires_at: Option<DateTime<Utc>>,
+        encryption_key: &[u8; 32],
+    ) -> (Self, GeneratedApiKey) {
         let mut key_bytes = [0u8; 32];
         rand::thread_rng().fill_bytes(&mut key_bytes);
 
@@ -40,6 +174,10 @@ impl ApiKey {
             .expect("failed to hash")
             .to_string();
 
+        let mut secret_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret_bytes);
+        let signing_secret = URL_SAFE_NO_PAD.encode(secret_bytes);
+
         let id = Uuid::new_v4();
 
         let api_key = Self {
@@ -47,19 +185,61 @@ impl ApiKey {
             business_id,
             key_hash,
             key_prefix: prefix.clone(),
-            name: None,
+            name: name.clone(),
+            scopes: scopes.clone(),
             rate_limit_per_minute: 100,
             created_at: Utc::now(),
-            expires_at: None,
+            expires_at,
             revoked_at: None,
             last_used_at: None,
+            signing_secret: Some(seal_signing_secret(encryption_key, id, &signing_secret)),
         };
 
-        let generated = GeneratedApiKey { id, key, prefix };
+        let generated = GeneratedApiKey {
+            id,
+            key,
+            prefix,
+            signing_secret,
+            name,
+            scopes,
+            expires_at,
+        };
 
         (api_key, generated)
     }
 
+    /// The plaintext signing secret, if the key has one. Secrets stored
+    /// before encryption are returned as they are until they get sealed.
+    pub fn open_signing_secret(&self, encryption_key: &[u8; 32]) -> anyhow::Result<Option<String>> {
+        let Some(stored) = self.signing_secret.as_deref() else {
+            return Ok(None);
+        };
+        let Some(encoded) = stored.strip_prefix(SEALED_PREFIX) else {
+            return Ok(Some(stored.to_string()));
+        };
+
+        let sealed = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .context("malformed sealed signing secret")?;
+        if sealed.len() < NONCE_LEN {
+            anyhow::bail!("malformed sealed signing secret");
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = Aes256Gcm::new(encryption_key.into())
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: self.id.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow::anyhow!("cannot decrypt signing secret of api key {}", self.id))?;
+
+        Ok(Some(
+            String::from_utf8(plaintext).context("signing secret is not UTF-8")?,
+        ))
+    }
+
     pub fn verify(&self, key: &str) -> bool {
         let parsed = match PasswordHash::new(&self.key_hash) {
             Ok(h) => h,
@@ -70,6 +250,10 @@ impl ApiKey {
             .is_ok()
     }
 
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
     pub fn is_valid(&self) -> bool {
         if self.revoked_at.is_some() {
             return false;
@@ -83,7 +267,72 @@ impl ApiKey {
     }
 }
 
+/// The string a signed request's `X-Payx-Signature` covers: the method, the
+/// path with its query string, the Unix timestamp, the nonce and the hex
+/// SHA-256 of the body, one per line.
+pub fn request_signing_string(
+    method: &str,
+    path_and_query: &str,
+    timestamp: i64,
+    nonce: &str,
+    body: &[u8],
+) -> String {
+    format!(
+        "{}\n{}\n{}\n{}\n{}",
+        method,
+        path_and_query,
+        timestamp,
+        nonce,
+        hex::encode(Sha256::digest(body))
+    )
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateApiKeyRequest {
     pub name: Option<String>,
+    /// Defaults to the scopes of the key making the request.
+    pub scopes: Option<Vec<Scope>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateApiKeyRequest {
+    /// How long the old key keeps working after the rotation. Defaults to
+    /// 24 hours; `0` retires it immediately.
+    pub overlap_seconds: Option<i64>,
+    /// Expiry of the replacement key.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Marks a signing secret sealed by [`seal_signing_secret`]. Secrets are
+/// URL-safe base64, so a plaintext one never starts with it.
+pub const SEALED_PREFIX: &str = "v1:";
+
+const NONCE_LEN: usize = 12;
+
+/// Encrypts a signing secret for storage with AES-256-GCM under
+/// `SECRET_ENCRYPTION_KEY`.
+///
+/// The API key itself is only kept as an Argon2 hash, but the server needs the
+/// signing secret back to verify signatures, so it is encrypted rather than
+/// hashed: a database dump or backup alone is not enough to sign requests. The
+/// key's ID is bound in as associated data, so a sealed secret copied onto
+/// another key's row does not open.
+pub fn seal_signing_secret(encryption_key: &[u8; 32], id: Uuid, secret: &str) -> String {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let ciphertext = Aes256Gcm::new(encryption_key.into())
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: secret.as_bytes(),
+                aad: id.as_bytes(),
+            },
+        )
+        .expect("failed to encrypt");
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    format!("{}{}", SEALED_PREFIX, URL_SAFE_NO_PAD.encode(sealed))
 }
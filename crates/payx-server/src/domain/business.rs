@@ -10,6 +10,8 @@ pub struct Business {
     pub webhook_url: Option<String>,
     #[serde(skip)]
     pub webhook_secret: Option<String>,
+    /// IANA timezone that daily balances are closed in.
+    pub timezone: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -19,10 +21,12 @@ pub struct CreateBusinessRequest {
     pub name: String,
     pub email: String,
     pub webhook_url: Option<String>,
+    pub timezone: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateBusinessRequest {
     pub name: Option<String>,
     pub webhook_url: Option<String>,
+    pub timezone: Option<String>,
 }
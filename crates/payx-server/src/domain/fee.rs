@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::TransactionType;
+use crate::ledger::LEDGER_SCALE;
+
+/// A business's fee for one transaction type in one currency:
+/// `flat_amount + amount * percentage / 100`, clamped to `min_amount` and
+/// `max_amount`.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct FeeRule {
+    pub id: Uuid,
+    pub business_id: Uuid,
+    pub transaction_type: TransactionType,
+    pub currency: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub flat_amount: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub percentage: Decimal,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub min_amount: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub max_amount: Option<Decimal>,
+    pub revenue_account_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FeeRule {
+    /// The fee `payer_account_id` owes on a transaction of `amount`.
+    /// Percentages are rounded to the ledger's scale with banker's rounding.
+    pub fn charge(&self, payer_account_id: Uuid, amount: Decimal) -> TransactionFee {
+        let mut percentage_amount = (amount * self.percentage / Decimal::ONE_HUNDRED)
+            .round_dp_with_strategy(LEDGER_SCALE, RoundingStrategy::MidpointNearestEven);
+        percentage_amount.rescale(LEDGER_SCALE);
+
+        let mut total = self.flat_amount + percentage_amount;
+        if let Some(min) = self.min_amount {
+            total = total.max(min);
+        }
+        if let Some(max) = self.max_amount {
+            total = total.min(max);
+        }
+        total.rescale(LEDGER_SCALE);
+
+        TransactionFee {
+            fee_rule_id: self.id,
+            payer_account_id,
+            revenue_account_id: self.revenue_account_id,
+            flat_amount: self.flat_amount,
+            percentage_amount,
+            amount: total,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFeeRuleRequest {
+    pub transaction_type: TransactionType,
+    pub currency: String,
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub flat_amount: Decimal,
+    /// Percent of the transaction amount, e.g. `1.5` for 1.5%.
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub percentage: Decimal,
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub min_amount: Option<Decimal>,
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub max_amount: Option<Decimal>,
+    pub revenue_account_id: Uuid,
+}
+
+/// A fee charged on a transaction. `amount` is what was posted, after the
+/// rule's minimum and maximum were applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionFee {
+    pub fee_rule_id: Uuid,
+    pub payer_account_id: Uuid,
+    pub revenue_account_id: Uuid,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub flat_amount: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub percentage_amount: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+}
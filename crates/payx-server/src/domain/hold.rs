@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum HoldStatus {
+    Active,
+    Captured,
+    Voided,
+    Expired,
+}
+
+impl HoldStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Captured => "captured",
+            Self::Voided => "voided",
+            Self::Expired => "expired",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Hold {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub account_id: Uuid,
+    pub destination_account_id: Option<Uuid>,
+    pub amount: Decimal,
+    pub captured_amount: Option<Decimal>,
+    pub currency: String,
+    pub status: HoldStatus,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+impl Hold {
+    pub fn is_capturable(&self, now: DateTime<Utc>) -> bool {
+        self.status == HoldStatus::Active && self.expires_at > now
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateHoldRequest {
+    pub source_account_id: Uuid,
+    pub destination_account_id: Option<Uuid>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub currency: String,
+    pub description: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CaptureHoldRequest {
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub amount: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HoldResponse {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub account_id: Uuid,
+    pub destination_account_id: Option<Uuid>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub captured_amount: Option<Decimal>,
+    pub currency: String,
+    pub status: HoldStatus,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+impl From<Hold> for HoldResponse {
+    fn from(h: Hold) -> Self {
+        Self {
+            id: h.id,
+            transaction_id: h.transaction_id,
+            account_id: h.account_id,
+            destination_account_id: h.destination_account_id,
+            amount: h.amount,
+            captured_amount: h.captured_amount,
+            currency: h.currency,
+            status: h.status,
+            expires_at: h.expires_at,
+            created_at: h.created_at,
+            released_at: h.released_at,
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// An account whose stored balances disagree with its ledger entries.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AccountDrift {
+    pub account_id: Uuid,
+    pub business_id: Uuid,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub ledger_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub stored_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub expected_available: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub stored_available: Decimal,
+}
+
+/// A ledger entry whose `balance_after` does not follow from the previous
+/// entry on the same account.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BalanceChainBreak {
+    pub entry_id: Uuid,
+    pub account_id: Uuid,
+    pub business_id: Uuid,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub expected_balance_after: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance_after: Decimal,
+}
+
+/// A completed transaction whose debit and credit entries do not match.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct UnbalancedTransaction {
+    pub transaction_id: Uuid,
+    pub business_ids: Vec<Uuid>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub debits: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub credits: Decimal,
+}
+
+const ACCOUNT_DRIFT: &str = "account_drift";
+const BALANCE_CHAIN_BREAK: &str = "balance_chain_break";
+const UNBALANCED_TRANSACTION: &str = "unbalanced_transaction";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub checked_at: DateTime<Utc>,
+    pub accounts_checked: i64,
+    pub transactions_checked: i64,
+    pub account_drifts: Vec<AccountDrift>,
+    pub balance_chain_breaks: Vec<BalanceChainBreak>,
+    pub unbalanced_transactions: Vec<UnbalancedTransaction>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.account_drifts.is_empty()
+            && self.balance_chain_breaks.is_empty()
+            && self.unbalanced_transactions.is_empty()
+    }
+
+    /// The subset of violations that touch the given business.
+    pub fn for_business(&self, business_id: Uuid) -> Self {
+        Self {
+            checked_at: self.checked_at,
+            accounts_checked: self.accounts_checked,
+            transactions_checked: self.transactions_checked,
+            account_drifts: self
+                .account_drifts
+                .iter()
+                .filter(|d| d.business_id == business_id)
+                .cloned()
+                .collect(),
+            balance_chain_breaks: self
+                .balance_chain_breaks
+                .iter()
+                .filter(|b| b.business_id == business_id)
+                .cloned()
+                .collect(),
+            unbalanced_transactions: self
+                .unbalanced_transactions
+                .iter()
+                .filter(|t| t.business_ids.contains(&business_id))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// `(business_id, kind, id)` of every violation, once for each business
+    /// it touches. Identifies a violation across checks.
+    pub fn violation_keys(&self) -> Vec<(Uuid, &'static str, Uuid)> {
+        self.account_drifts
+            .iter()
+            .map(|d| (d.business_id, ACCOUNT_DRIFT, d.account_id))
+            .chain(
+                self.balance_chain_breaks
+                    .iter()
+                    .map(|b| (b.business_id, BALANCE_CHAIN_BREAK, b.entry_id)),
+            )
+            .chain(self.unbalanced_transactions.iter().flat_map(|t| {
+                t.business_ids
+                    .iter()
+                    .map(|b| (*b, UNBALANCED_TRANSACTION, t.transaction_id))
+            }))
+            .collect()
+    }
+
+    /// Kinds with at least `limit` violations, whose list may have been cut
+    /// off at that limit and so may not contain every violation of the kind.
+    pub fn truncated_kinds(&self, limit: usize) -> Vec<&'static str> {
+        [
+            (ACCOUNT_DRIFT, self.account_drifts.len()),
+            (BALANCE_CHAIN_BREAK, self.balance_chain_breaks.len()),
+            (UNBALANCED_TRANSACTION, self.unbalanced_transactions.len()),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count >= limit)
+        .map(|(kind, _)| kind)
+        .collect()
+    }
+
+    /// The subset of violations for which `keep(kind, id)` holds.
+    pub fn filtered(&self, keep: impl Fn(&str, Uuid) -> bool) -> Self {
+        Self {
+            checked_at: self.checked_at,
+            accounts_checked: self.accounts_checked,
+            transactions_checked: self.transactions_checked,
+            account_drifts: self
+                .account_drifts
+                .iter()
+                .filter(|d| keep(ACCOUNT_DRIFT, d.account_id))
+                .cloned()
+                .collect(),
+            balance_chain_breaks: self
+                .balance_chain_breaks
+                .iter()
+                .filter(|b| keep(BALANCE_CHAIN_BREAK, b.entry_id))
+                .cloned()
+                .collect(),
+            unbalanced_transactions: self
+                .unbalanced_transactions
+                .iter()
+                .filter(|t| keep(UNBALANCED_TRANSACTION, t.transaction_id))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn business_ids(&self) -> Vec<Uuid> {
+        let mut ids: Vec<Uuid> = self
+            .account_drifts
+            .iter()
+            .map(|d| d.business_id)
+            .chain(self.balance_chain_breaks.iter().map(|b| b.business_id))
+            .chain(
+                self.unbalanced_transactions
+                    .iter()
+                    .flat_map(|t| t.business_ids.iter().copied()),
+            )
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+}
+
+/// The first ledger entry whose hash does not follow from its contents and
+/// the entry before it. The entry is absent when the account's chain head
+/// names entries that are all missing.
+#[derive(Debug, Clone, Serialize)]
+pub struct HashChainBreak {
+    pub entry_id: Option<Uuid>,
+    pub seq: Option<i64>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainVerification {
+    pub account_id: Uuid,
+    pub entries_checked: i64,
+    pub valid: bool,
+    pub first_break: Option<HashChainBreak>,
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Days per year used to turn an annual rate into a daily one.
+pub const INTEREST_DAY_COUNT: u32 = 365;
+
+/// Scale daily accruals are kept at before the monthly posting rounds them
+/// to the ledger's scale.
+pub const ACCRUAL_SCALE: u32 = 8;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AccountInterest {
+    pub account_id: Uuid,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub annual_rate: Decimal,
+    pub expense_account_id: Uuid,
+    pub accrued_through: NaiveDate,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetAccountInterestRequest {
+    /// Annual rate as a fraction, e.g. `0.025` for 2.5%.
+    #[serde(with = "rust_decimal::serde::str")]
+    pub annual_rate: Decimal,
+    pub expense_account_id: Uuid,
+}
+
+/// One day's interest on an account.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct InterestAccrual {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub accrual_date: NaiveDate,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub annual_rate: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub transaction_id: Option<Uuid>,
+    pub posted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
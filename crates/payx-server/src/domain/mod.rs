@@ -1,11 +1,21 @@
 mod account;
 mod api_key;
 mod business;
+mod fee;
+mod hold;
+mod integrity;
+mod interest;
+mod statement;
 mod transaction;
 mod webhook;
 
 pub use account::*;
 pub use api_key::*;
 pub use business::*;
+pub use fee::*;
+pub use hold::*;
+pub use integrity::*;
+pub use interest::*;
+pub use statement::*;
 pub use transaction::*;
 pub use webhook::*;
@@ -0,0 +1,110 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{EntryType, TransactionType};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// One ledger entry on a statement. `balance` is the account's running
+/// balance after the entry.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct StatementLine {
+    pub entry_id: Uuid,
+    pub transaction_id: Uuid,
+    pub transaction_type: TransactionType,
+    pub description: Option<String>,
+    pub entry_type: EntryType,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An account's activity over whole days in its business's timezone.
+#[derive(Debug, Serialize)]
+pub struct AccountStatement {
+    pub account_id: Uuid,
+    pub currency: String,
+    pub timezone: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub opening_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_credits: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_debits: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub closing_balance: Decimal,
+    pub lines: Vec<StatementLine>,
+}
+
+impl AccountStatement {
+    /// Renders the statement as CSV: an opening balance row, one row per
+    /// line, then credit and debit totals and the closing balance.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "date,entry_id,transaction_id,transaction_type,description,entry_type,amount,balance\n",
+        );
+
+        csv.push_str(&format!(
+            "{},,,,Opening balance,,,{}\n",
+            self.from, self.opening_balance
+        ));
+
+        for line in &self.lines {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                line.created_at.to_rfc3339(),
+                line.entry_id,
+                line.transaction_id,
+                line.transaction_type.as_str(),
+                csv_field(line.description.as_deref().unwrap_or("")),
+                line.entry_type.as_str(),
+                line.amount,
+                line.balance
+            ));
+        }
+
+        csv.push_str(&format!(
+            "{},,,,Total credits,credit,{},\n",
+            self.to, self.total_credits
+        ));
+        csv.push_str(&format!(
+            "{},,,,Total debits,debit,{},\n",
+            self.to, self.total_debits
+        ));
+        csv.push_str(&format!(
+            "{},,,,Closing balance,,,{}\n",
+            self.to, self.closing_balance
+        ));
+
+        csv
+    }
+}
+
+/// Quotes a field containing a delimiter, quote or line break. A field that
+/// a spreadsheet would read as a formula is prefixed with `'` first, so a
+/// description such as `=HYPERLINK(...)` opens as text.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
@@ -1,8 +1,16 @@
 use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use super::TransactionFee;
+use crate::error::ErrorBody;
+
+/// `prev_hash` of the first hashed entry on an account.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "varchar", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -10,6 +18,27 @@ pub enum TransactionType {
     Credit,
     Debit,
     Transfer,
+    Reversal,
+    Journal,
+    /// An account's initial balance, posted when the account is created.
+    Opening,
+    /// A month's interest, posted from the account's expense account by the
+    /// interest accrual worker.
+    Interest,
+}
+
+impl TransactionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Credit => "credit",
+            Self::Debit => "debit",
+            Self::Transfer => "transfer",
+            Self::Reversal => "reversal",
+            Self::Journal => "journal",
+            Self::Opening => "opening",
+            Self::Interest => "interest",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
@@ -19,11 +48,14 @@ pub enum TransactionStatus {
     Pending,
     Completed,
     Failed,
+    Voided,
+    Expired,
 }
 
 #[derive(Debug, Clone, Serialize, sqlx::FromRow)]
 pub struct Transaction {
     pub id: Uuid,
+    pub business_id: Uuid,
     pub idempotency_key: Option<String>,
     #[sqlx(rename = "type")]
     pub tx_type: TransactionType,
@@ -36,6 +68,8 @@ pub struct Transaction {
     pub metadata: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub reversed_transaction_id: Option<Uuid>,
+    pub fees: sqlx::types::Json<Vec<TransactionFee>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -49,6 +83,74 @@ pub struct CreateTransactionRequest {
     pub currency: String,
     pub description: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub legs: Vec<JournalLeg>,
+}
+
+/// `atomic` posts every item or none; `independent` posts each item on its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchMode {
+    #[default]
+    Atomic,
+    Independent,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchTransactionItem {
+    pub idempotency_key: Option<String>,
+    #[serde(flatten)]
+    pub request: CreateTransactionRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchTransactionRequest {
+    #[serde(default)]
+    pub mode: BatchMode,
+    pub transactions: Vec<BatchTransactionItem>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EntryType {
+    Debit,
+    Credit,
+}
+
+impl EntryType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Debit => "debit",
+            Self::Credit => "credit",
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Debit => Self::Credit,
+            Self::Credit => Self::Debit,
+        }
+    }
+}
+
+/// One side of a journal transaction. `currency` defaults to the
+/// transaction's currency.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournalLeg {
+    pub account_id: Uuid,
+    pub entry_type: EntryType,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReverseTransactionRequest {
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub amount: Option<Decimal>,
+    pub description: Option<String>,
+    pub metadata: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -65,6 +167,8 @@ pub struct TransactionResponse {
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub reversed_transaction_id: Option<Uuid>,
+    pub fees: Vec<TransactionFee>,
 }
 
 impl From<Transaction> for TransactionResponse {
@@ -80,6 +184,8 @@ impl From<Transaction> for TransactionResponse {
             description: t.description,
             created_at: t.created_at,
             completed_at: t.completed_at,
+            reversed_transaction_id: t.reversed_transaction_id,
+            fees: t.fees.0,
         }
     }
 }
@@ -89,8 +195,101 @@ pub struct LedgerEntry {
     pub id: Uuid,
     pub transaction_id: Uuid,
     pub account_id: Uuid,
-    pub entry_type: String,
+    pub entry_type: EntryType,
     pub amount: Decimal,
     pub balance_after: Decimal,
     pub created_at: DateTime<Utc>,
+    pub seq: i64,
+    pub prev_hash: Option<String>,
+    pub hash: Option<String>,
+}
+
+impl LedgerEntry {
+    /// HMAC-SHA256 of the entry's contents chained to `prev_hash`, keyed with
+    /// the server's ledger hash key. Amounts and timestamps must be at the
+    /// precision the database stores them.
+    ///
+    /// The key never reaches the database, so someone who can write to it
+    /// can still edit entries but cannot recompute hashes that verify. A
+    /// plain digest would not stop them: the chain could simply be rebuilt
+    /// after the edit, as the migrations that reclassified accounts did.
+    pub fn compute_hash(&self, prev_hash: &str, key: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+        mac.update(self.hash_contents(prev_hash).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// The unkeyed SHA-256 hash entries were chained with before hashes were
+    /// keyed. Only used to verify those chains before rewriting them.
+    pub fn compute_unkeyed_hash(&self, prev_hash: &str) -> String {
+        hex::encode(Sha256::digest(self.hash_contents(prev_hash).as_bytes()))
+    }
+
+    fn hash_contents(&self, prev_hash: &str) -> String {
+        format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}",
+            prev_hash,
+            self.id,
+            self.transaction_id,
+            self.account_id,
+            self.entry_type.as_str(),
+            self.amount.normalize(),
+            self.balance_after.normalize(),
+            self.created_at.timestamp_micros()
+        )
+    }
+}
+
+/// Keyed hash naming `hash` as the latest entry on the account, stored as
+/// `accounts.ledger_head`. Entries deleted from the end of a chain leave the
+/// rest of it intact, but the head then names an entry that is gone.
+pub fn ledger_head(account_id: Uuid, hash: &str, key: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(format!("head|{}|{}", account_id, hash).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[derive(Debug, Serialize)]
+pub struct LedgerEntryResponse {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub account_id: Uuid,
+    pub entry_type: EntryType,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance_after: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub hash: Option<String>,
+}
+
+impl From<LedgerEntry> for LedgerEntryResponse {
+    fn from(e: LedgerEntry) -> Self {
+        Self {
+            id: e.id,
+            transaction_id: e.transaction_id,
+            account_id: e.account_id,
+            entry_type: e.entry_type,
+            amount: e.amount,
+            balance_after: e.balance_after,
+            created_at: e.created_at,
+            hash: e.hash,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<TransactionResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorBody>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchTransactionResponse {
+    pub mode: BatchMode,
+    pub results: Vec<BatchItemResult>,
 }
@@ -6,12 +6,15 @@ use serde::Serialize;
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::domain::Scope;
+
 #[derive(Error, Debug)]
 pub enum AppError {
-    #[error("insufficient funds: available {available}, requested {requested}")]
+    #[error("insufficient funds: available {available}, overdraft limit {overdraft_limit}, requested {requested}")]
     InsufficientFunds {
         account_id: Uuid,
         available: Decimal,
+        overdraft_limit: Decimal,
         requested: Decimal,
     },
 
@@ -24,6 +27,33 @@ pub enum AppError {
     #[error("transaction not found: {0}")]
     TransactionNotFound(Uuid),
 
+    #[error("external id already in use: {0}")]
+    DuplicateExternalId(String),
+
+    #[error("account {account_id} is {status}")]
+    AccountNotActive { account_id: Uuid, status: String },
+
+    #[error("account {account_id} cannot move from {from} to {to}")]
+    InvalidAccountTransition {
+        account_id: Uuid,
+        from: String,
+        to: String,
+    },
+
+    #[error("{limit} limit exceeded: limit {max}, attempted {attempted}")]
+    LimitExceeded {
+        account_id: Uuid,
+        limit: &'static str,
+        max: Decimal,
+        attempted: Decimal,
+    },
+
+    #[error("hold not found: {0}")]
+    HoldNotFound(Uuid),
+
+    #[error("hold {hold_id} is not active: {status}")]
+    HoldNotActive { hold_id: Uuid, status: String },
+
     #[error("not found: {0}")]
     NotFound(String),
 
@@ -42,6 +72,12 @@ pub enum AppError {
     #[error("invalid api key")]
     InvalidApiKey,
 
+    #[error("invalid request signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("api key lacks the {0} scope")]
+    InsufficientScope(Scope),
+
     #[error("rate limit exceeded")]
     RateLimitExceeded,
 
@@ -56,6 +92,9 @@ pub enum AppError {
 
     #[error("internal error: {0}")]
     Internal(#[from] anyhow::Error),
+
+    #[error("batch item {index}: {error}")]
+    BatchItemFailed { index: usize, error: Box<AppError> },
 }
 
 #[derive(Serialize)]
@@ -63,19 +102,20 @@ struct ErrorResponse {
     error: ErrorBody,
 }
 
-#[derive(Serialize)]
-struct ErrorBody {
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
     code: &'static str,
     message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     details: Option<serde_json::Value>,
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, code, details) = match &self {
+impl AppError {
+    fn parts(&self) -> (StatusCode, &'static str, Option<serde_json::Value>) {
+        match self {
             Self::InsufficientFunds {
                 available,
+                overdraft_limit,
                 requested,
                 ..
             } => (
@@ -83,18 +123,46 @@ impl IntoResponse for AppError {
                 "insufficient_funds",
                 Some(serde_json::json!({
                     "available": available.to_string(),
+                    "overdraft_limit": overdraft_limit.to_string(),
                     "requested": requested.to_string()
                 })),
             ),
             Self::AccountNotFound(_) => (StatusCode::NOT_FOUND, "account_not_found", None),
             Self::BusinessNotFound(_) => (StatusCode::NOT_FOUND, "business_not_found", None),
             Self::TransactionNotFound(_) => (StatusCode::NOT_FOUND, "transaction_not_found", None),
+            Self::DuplicateExternalId(_) => (StatusCode::CONFLICT, "duplicate_external_id", None),
+            Self::AccountNotActive { .. } => (StatusCode::CONFLICT, "account_not_active", None),
+            Self::InvalidAccountTransition { .. } => {
+                (StatusCode::CONFLICT, "invalid_account_transition", None)
+            }
+            Self::LimitExceeded {
+                limit,
+                max,
+                attempted,
+                ..
+            } => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "limit_exceeded",
+                Some(serde_json::json!({
+                    "limit": limit,
+                    "max": max.to_string(),
+                    "attempted": attempted.to_string()
+                })),
+            ),
+            Self::HoldNotFound(_) => (StatusCode::NOT_FOUND, "hold_not_found", None),
+            Self::HoldNotActive { .. } => (StatusCode::CONFLICT, "hold_not_active", None),
             Self::NotFound(_) => (StatusCode::NOT_FOUND, "not_found", None),
             Self::CurrencyMismatch { .. } => (StatusCode::BAD_REQUEST, "currency_mismatch", None),
             Self::IdempotencyConflict { .. } => {
                 (StatusCode::CONFLICT, "idempotency_conflict", None)
             }
             Self::InvalidApiKey => (StatusCode::UNAUTHORIZED, "invalid_api_key", None),
+            Self::InvalidSignature(_) => (StatusCode::UNAUTHORIZED, "invalid_signature", None),
+            Self::InsufficientScope(scope) => (
+                StatusCode::FORBIDDEN,
+                "insufficient_scope",
+                Some(serde_json::json!({ "required_scope": scope })),
+            ),
             Self::RateLimitExceeded => (StatusCode::TOO_MANY_REQUESTS, "rate_limit_exceeded", None),
             Self::Validation(_) => (StatusCode::BAD_REQUEST, "validation_error", None),
             Self::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "database_error", None),
@@ -104,17 +172,33 @@ impl IntoResponse for AppError {
                 None,
             ),
             Self::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", None),
-        };
+            Self::BatchItemFailed { index, error } => {
+                let (status, code, details) = error.parts();
+                let mut details = details.unwrap_or_else(|| serde_json::json!({}));
+                details["index"] = serde_json::json!(index);
+                (status, code, Some(details))
+            }
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.parts().0
+    }
 
-        let body = ErrorResponse {
-            error: ErrorBody {
-                code,
-                message: self.to_string(),
-                details,
-            },
-        };
+    pub fn body(&self) -> ErrorBody {
+        let (_, code, details) = self.parts();
+        ErrorBody {
+            code,
+            message: self.to_string(),
+            details,
+        }
+    }
+}
 
-        (status, Json(body)).into_response()
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let body = ErrorResponse { error: self.body() };
+        (self.status(), Json(body)).into_response()
     }
 }
 
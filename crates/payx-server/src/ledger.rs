@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::{PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::domain::{
+    constant_time_eq, ledger_head, Account, AccountType, ChainVerification, EntryType,
+    HashChainBreak, LedgerEntry, GENESIS_HASH,
+};
+use crate::error::{AppError, Result};
+
+/// Scale of the `amount` and `balance_after` columns.
+pub const LEDGER_SCALE: u32 = 4;
+
+const VERIFY_BATCH_SIZE: i64 = 1000;
+
+static HASH_KEY: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Sets the key ledger hashes are computed with. Every process that posts or
+/// verifies entries must use the same key, and it can only be set once.
+pub fn init_hash_key(key: &str) -> anyhow::Result<()> {
+    if HASH_KEY.get_or_init(|| key.as_bytes().to_vec()) != key.as_bytes() {
+        anyhow::bail!("ledger hash key is already set to a different key");
+    }
+    Ok(())
+}
+
+fn hash_key() -> Result<&'static [u8]> {
+    HASH_KEY
+        .get()
+        .map(Vec::as_slice)
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("ledger hash key is not set")))
+}
+
+/// Appends an entry to the account's ledger, chaining its hash to the
+/// account's latest entry, and moves the account's chain head to it. The
+/// caller must hold the account's row lock.
+pub async fn post_entry(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    transaction_id: Uuid,
+    account_id: Uuid,
+    entry_type: EntryType,
+    amount: Decimal,
+    balance_after: Decimal,
+    created_at: DateTime<Utc>,
+) -> Result<LedgerEntry> {
+    let prev_hash: Option<String> = sqlx::query_scalar(
+        "SELECT hash FROM ledger_entries WHERE account_id = $1 ORDER BY seq DESC LIMIT 1",
+    )
+    .bind(account_id)
+    .fetch_optional(&mut **tx)
+    .await?
+    .flatten();
+    let prev_hash = prev_hash.unwrap_or_else(|| GENESIS_HASH.to_string());
+
+    // Hash exactly what the database will store
+    let mut entry = LedgerEntry {
+        id: Uuid::new_v4(),
+        transaction_id,
+        account_id,
+        entry_type,
+        amount: amount.round_dp_with_strategy(LEDGER_SCALE, RoundingStrategy::MidpointAwayFromZero),
+        balance_after: balance_after
+            .round_dp_with_strategy(LEDGER_SCALE, RoundingStrategy::MidpointAwayFromZero),
+        created_at: DateTime::from_timestamp_micros(created_at.timestamp_micros())
+            .unwrap_or(created_at),
+        seq: 0,
+        prev_hash: None,
+        hash: None,
+    };
+    let key = hash_key()?;
+    let hash = entry.compute_hash(&prev_hash, key);
+    let head = ledger_head(account_id, &hash, key);
+    entry.prev_hash = Some(prev_hash);
+    entry.hash = Some(hash);
+
+    let entry: LedgerEntry = sqlx::query_as(
+        r#"
+        INSERT INTO ledger_entries (id, transaction_id, account_id, entry_type, amount, balance_after, created_at, prev_hash, hash)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(entry.id)
+    .bind(entry.transaction_id)
+    .bind(entry.account_id)
+    .bind(entry.entry_type)
+    .bind(entry.amount)
+    .bind(entry.balance_after)
+    .bind(entry.created_at)
+    .bind(&entry.prev_hash)
+    .bind(&entry.hash)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    sqlx::query("UPDATE accounts SET ledger_head = $1 WHERE id = $2")
+        .bind(head)
+        .bind(account_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(entry)
+}
+
+/// Locks the given accounts with `FOR UPDATE`.
+pub async fn lock_accounts(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    account_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Account>> {
+    // Lock in consistent order to prevent deadlocks
+    let mut account_ids = account_ids.to_vec();
+    account_ids.sort();
+    account_ids.dedup();
+
+    let mut accounts = HashMap::with_capacity(account_ids.len());
+    for account_id in account_ids {
+        let account: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1 FOR UPDATE")
+            .bind(account_id)
+            .fetch_optional(&mut **tx)
+            .await?
+            .ok_or(AppError::AccountNotFound(account_id))?;
+        accounts.insert(account_id, account);
+    }
+
+    Ok(accounts)
+}
+
+/// Returns the ID of the business's settlement account for `currency`,
+/// creating the account on first use. Does not lock it.
+pub async fn settlement_account_id(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    business_id: Uuid,
+    currency: &str,
+) -> Result<Uuid> {
+    sqlx::query(
+        r#"
+        INSERT INTO accounts (id, business_id, account_type, is_settlement, currency, balance, available_balance, version, created_at, updated_at)
+        VALUES ($1, $2, $3, TRUE, $4, 0, 0, 0, NOW(), NOW())
+        ON CONFLICT (business_id, currency) WHERE is_settlement DO NOTHING
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(business_id)
+    .bind(AccountType::Asset)
+    .bind(currency)
+    .execute(&mut **tx)
+    .await?;
+
+    let id = sqlx::query_scalar(
+        "SELECT id FROM accounts WHERE business_id = $1 AND currency = $2 AND is_settlement",
+    )
+    .bind(business_id)
+    .bind(currency)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(id)
+}
+
+/// Posts the settlement side of a credit or debit and updates the
+/// settlement account's balances. The caller must hold its row lock.
+pub async fn post_settlement(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    settlement: &Account,
+    transaction_id: Uuid,
+    entry_type: EntryType,
+    amount: Decimal,
+    created_at: DateTime<Utc>,
+) -> Result<LedgerEntry> {
+    let delta = settlement.balance_delta(entry_type, amount);
+
+    sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
+        .bind(settlement.balance + delta)
+        .bind(settlement.available_balance + delta)
+        .bind(created_at)
+        .bind(settlement.id)
+        .execute(&mut **tx)
+        .await?;
+
+    post_entry(
+        tx,
+        transaction_id,
+        settlement.id,
+        entry_type,
+        amount,
+        settlement.balance + delta,
+        created_at,
+    )
+    .await
+}
+
+/// The account's ledger balance at `at`, from the last entry posted by then.
+pub async fn balance_at(pool: &PgPool, account: &Account, at: DateTime<Utc>) -> Result<Decimal> {
+    let last: Option<LedgerEntry> = sqlx::query_as(
+        r#"
+        SELECT * FROM ledger_entries
+        WHERE account_id = $1 AND created_at <= $2
+        ORDER BY seq DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(account.id)
+    .bind(at)
+    .fetch_optional(pool)
+    .await?;
+
+    // Every balance, including an opening balance, is posted to the ledger
+    Ok(last.map_or(Decimal::new(0, LEDGER_SCALE), |entry| entry.balance_after))
+}
+
+/// How long after a day ends before its closing balance is read. Entries are
+/// stamped when their transaction starts, so one started just before midnight
+/// can still commit after it.
+pub const DAY_CLOSE_GRACE: TimeDelta = TimeDelta::minutes(5);
+
+/// The last day in `tz` that ended at least [`DAY_CLOSE_GRACE`] before `now`.
+pub fn last_closed_day(tz: Tz, now: DateTime<Utc>) -> NaiveDate {
+    (now - DAY_CLOSE_GRACE).with_timezone(&tz).date_naive() - Days::new(1)
+}
+
+/// Start of `date` in `tz`. Where a DST change skips midnight the day
+/// starts an hour later.
+pub fn day_start(tz: Tz, date: NaiveDate) -> DateTime<Utc> {
+    tz.from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&date.and_hms_opt(1, 0, 0).expect("01:00 is valid"))
+                .earliest()
+        })
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| date.and_time(NaiveTime::MIN).and_utc())
+}
+
+/// Walks the account's hash chain in posting order and reports the first
+/// entry that does not verify. Entries written before hashing was enabled
+/// are skipped until the first hashed entry.
+///
+/// Hashes are keyed with a secret the database never sees, so this catches
+/// someone with write access to the database editing, inserting, reordering
+/// or deleting entries. Deleting entries from the end of the chain leaves a
+/// chain that verifies, and is caught by the account's `ledger_head` instead.
+/// Not caught: restoring an account's entries and head together to an
+/// earlier state copied beforehand, or anything done by someone who holds
+/// the key.
+pub async fn verify_chain(pool: &PgPool, account_id: Uuid) -> Result<ChainVerification> {
+    let key = hash_key()?;
+    let head: Option<String> =
+        sqlx::query_scalar::<_, Option<String>>("SELECT ledger_head FROM accounts WHERE id = $1")
+            .bind(account_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(AppError::AccountNotFound(account_id))?;
+
+    let mut entries_checked = 0;
+    let mut last: Option<LedgerEntry> = None;
+    // Backfilled opening entries sit just below an account's first entry,
+    // which can put them at seq 0
+    let mut after_seq = i64::MIN;
+
+    loop {
+        let entries: Vec<LedgerEntry> = sqlx::query_as(
+            r#"
+            SELECT * FROM ledger_entries
+            WHERE account_id = $1 AND seq > $2
+            ORDER BY seq
+            LIMIT $3
+            "#,
+        )
+        .bind(account_id)
+        .bind(after_seq)
+        .bind(VERIFY_BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+
+        let Some(batch_last) = entries.last() else {
+            break;
+        };
+        after_seq = batch_last.seq;
+
+        for entry in entries {
+            let last_hash = last.as_ref().and_then(|e| e.hash.as_deref());
+            let Some(hash) = entry.hash.as_deref() else {
+                if last_hash.is_some() {
+                    return Ok(broken(
+                        account_id,
+                        entries_checked,
+                        Some(&entry),
+                        "entry is not hashed",
+                    ));
+                }
+                continue;
+            };
+
+            let expected_prev = last_hash.unwrap_or(GENESIS_HASH);
+            if entry.prev_hash.as_deref() != Some(expected_prev) {
+                return Ok(broken(
+                    account_id,
+                    entries_checked,
+                    Some(&entry),
+                    "prev_hash does not match the previous entry",
+                ));
+            }
+            if !constant_time_eq(
+                entry.compute_hash(expected_prev, key).as_bytes(),
+                hash.as_bytes(),
+            ) {
+                return Ok(broken(
+                    account_id,
+                    entries_checked,
+                    Some(&entry),
+                    "hash does not match entry contents",
+                ));
+            }
+
+            entries_checked += 1;
+            last = Some(entry);
+        }
+    }
+
+    if let Some(reason) = check_head(account_id, head.as_deref(), last.as_ref(), key) {
+        return Ok(broken(account_id, entries_checked, last.as_ref(), reason));
+    }
+
+    Ok(ChainVerification {
+        account_id,
+        entries_checked,
+        valid: true,
+        first_break: None,
+    })
+}
+
+/// Rewrites the account's hash chain with keyed hashes, for chains posted
+/// before hashes were keyed, and reports the first entry that stops it.
+///
+/// Unkeyed hashes are accepted only ahead of the first keyed entry, and only
+/// if they still verify, so a chain that was already edited is reported and
+/// left as it is. Whatever the unkeyed entries say when this runs is trusted
+/// from then on, so it is meant to be run once, right after upgrading.
+pub async fn rekey_chain(pool: &PgPool, account_id: Uuid) -> Result<ChainVerification> {
+    let key = hash_key()?;
+    let mut tx = pool.begin().await?;
+
+    let head: Option<String> = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT ledger_head FROM accounts WHERE id = $1 FOR UPDATE",
+    )
+    .bind(account_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::AccountNotFound(account_id))?;
+
+    let mut entries_checked = 0;
+    let mut last: Option<LedgerEntry> = None;
+    let mut rekeyed_hash: Option<String> = None;
+    let mut keyed = false;
+    // Backfilled opening entries sit just below an account's first entry,
+    // which can put them at seq 0
+    let mut after_seq = i64::MIN;
+
+    loop {
+        let entries: Vec<LedgerEntry> = sqlx::query_as(
+            r#"
+            SELECT * FROM ledger_entries
+            WHERE account_id = $1 AND seq > $2
+            ORDER BY seq
+            LIMIT $3
+            "#,
+        )
+        .bind(account_id)
+        .bind(after_seq)
+        .bind(VERIFY_BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let Some(batch_last) = entries.last() else {
+            break;
+        };
+        after_seq = batch_last.seq;
+
+        for entry in entries {
+            let last_hash = last.as_ref().and_then(|e| e.hash.as_deref());
+            let Some(hash) = entry.hash.as_deref() else {
+                if last_hash.is_some() {
+                    return Ok(broken(
+                        account_id,
+                        entries_checked,
+                        Some(&entry),
+                        "entry is not hashed",
+                    ));
+                }
+                continue;
+            };
+
+            let expected_prev = last_hash.unwrap_or(GENESIS_HASH);
+            if entry.prev_hash.as_deref() != Some(expected_prev) {
+                return Ok(broken(
+                    account_id,
+                    entries_checked,
+                    Some(&entry),
+                    "prev_hash does not match the previous entry",
+                ));
+            }
+            if constant_time_eq(
+                entry.compute_hash(expected_prev, key).as_bytes(),
+                hash.as_bytes(),
+            ) {
+                keyed = true;
+            } else if keyed || entry.compute_unkeyed_hash(expected_prev) != hash {
+                return Ok(broken(
+                    account_id,
+                    entries_checked,
+                    Some(&entry),
+                    "hash does not match entry contents",
+                ));
+            }
+
+            let prev_hash = rekeyed_hash.as_deref().unwrap_or(GENESIS_HASH);
+            let new_hash = entry.compute_hash(prev_hash, key);
+            if new_hash != hash {
+                sqlx::query("UPDATE ledger_entries SET prev_hash = $1, hash = $2 WHERE id = $3")
+                    .bind(prev_hash)
+                    .bind(&new_hash)
+                    .bind(entry.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            entries_checked += 1;
+            rekeyed_hash = Some(new_hash);
+            last = Some(entry);
+        }
+    }
+
+    // Chains with keyed entries have had a head since their first one.
+    if head.is_some() || keyed {
+        if let Some(reason) = check_head(account_id, head.as_deref(), last.as_ref(), key) {
+            return Ok(broken(account_id, entries_checked, last.as_ref(), reason));
+        }
+    }
+
+    if let Some(hash) = rekeyed_hash {
+        sqlx::query("UPDATE accounts SET ledger_head = $1 WHERE id = $2")
+            .bind(ledger_head(account_id, &hash, key))
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(ChainVerification {
+        account_id,
+        entries_checked,
+        valid: true,
+        first_break: None,
+    })
+}
+
+/// Why `head` does not name `last` as the account's latest hashed entry.
+fn check_head(
+    account_id: Uuid,
+    head: Option<&str>,
+    last: Option<&LedgerEntry>,
+    key: &[u8],
+) -> Option<&'static str> {
+    let expected = last
+        .and_then(|e| e.hash.as_deref())
+        .map(|hash| ledger_head(account_id, hash, key));
+
+    match (head, expected) {
+        (None, None) => None,
+        (None, Some(_)) => Some("chain head is missing"),
+        (Some(head), Some(expected)) if constant_time_eq(head.as_bytes(), expected.as_bytes()) => {
+            None
+        }
+        (Some(_), _) => Some("chain head does not match the latest entry"),
+    }
+}
+
+fn broken(
+    account_id: Uuid,
+    entries_checked: i64,
+    entry: Option<&LedgerEntry>,
+    reason: &str,
+) -> ChainVerification {
+    ChainVerification {
+        account_id,
+        entries_checked,
+        valid: false,
+        first_break: Some(HashChainBreak {
+            entry_id: entry.map(|e| e.id),
+            seq: entry.map(|e| e.seq),
+            reason: reason.to_string(),
+        }),
+    }
+}
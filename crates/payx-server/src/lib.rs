@@ -2,6 +2,7 @@ pub mod api;
 pub mod config;
 pub mod domain;
 pub mod error;
+pub mod ledger;
 pub mod telemetry;
 pub mod workers;
 
@@ -11,8 +12,14 @@ use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 use std::sync::Arc;
 
+use crate::api::middleware::key_cache::ApiKeyCache;
 use crate::api::routes;
 use crate::config::Config;
+use crate::workers::api_key_sync::ApiKeySync;
+use crate::workers::balance_snapshot::BalanceSnapshotWorker;
+use crate::workers::hold_expiry::HoldExpiryWorker;
+use crate::workers::integrity_checker::IntegrityChecker;
+use crate::workers::interest_accrual::InterestAccrualWorker;
 use crate::workers::webhook_processor::WebhookProcessor;
 
 #[derive(Clone)]
@@ -20,11 +27,17 @@ pub struct AppState {
     pub db: PgPool,
     pub config: Config,
     pub http_client: reqwest::Client,
+    pub api_keys: Arc<ApiKeyCache>,
 }
 
 pub struct App {
     state: Arc<AppState>,
     _webhook_processor: WebhookProcessor,
+    _hold_expiry: HoldExpiryWorker,
+    _integrity_checker: IntegrityChecker,
+    _interest_accrual: InterestAccrualWorker,
+    _balance_snapshot: BalanceSnapshotWorker,
+    _api_key_sync: ApiKeySync,
 }
 
 impl App {
@@ -39,23 +52,65 @@ impl App {
             .await?;
 
         sqlx::migrate!("./migrations").run(&db).await?;
+        ledger::init_hash_key(&config.ledger_hash_key)?;
+        api::handlers::api_keys::seal_signing_secrets(&db, &config.secret_encryption_key).await?;
 
         let http_client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(30))
             .build()?;
 
+        let api_keys = Arc::new(ApiKeyCache::new(std::time::Duration::from_secs(
+            config.api_key_cache_ttl_seconds,
+        )));
+
         let state = Arc::new(AppState {
             db: db.clone(),
             config: config.clone(),
             http_client: http_client.clone(),
+            api_keys: api_keys.clone(),
         });
 
-        let mut webhook_processor = WebhookProcessor::new(db, http_client);
+        let mut api_key_sync = ApiKeySync::new(
+            db.clone(),
+            api_keys,
+            std::time::Duration::from_secs(config.api_key_last_used_flush_seconds),
+            // A request stamped up to the skew ahead stays valid for twice it.
+            std::time::Duration::from_secs(2 * config.request_signature_max_skew_seconds),
+        );
+        api_key_sync.start();
+
+        let mut webhook_processor = WebhookProcessor::new(db.clone(), http_client);
         webhook_processor.start();
 
+        let mut hold_expiry = HoldExpiryWorker::new(db.clone());
+        hold_expiry.start();
+
+        let mut integrity_checker = IntegrityChecker::new(
+            db.clone(),
+            std::time::Duration::from_secs(config.integrity_check_interval_seconds),
+        );
+        integrity_checker.start();
+
+        let mut interest_accrual = InterestAccrualWorker::new(
+            db.clone(),
+            std::time::Duration::from_secs(config.interest_accrual_interval_seconds),
+        );
+        interest_accrual.start();
+
+        let mut balance_snapshot = BalanceSnapshotWorker::new(
+            db,
+            std::time::Duration::from_secs(config.balance_snapshot_interval_seconds),
+        );
+        balance_snapshot.start();
+
         Ok(Self {
             state,
             _webhook_processor: webhook_processor,
+            _hold_expiry: hold_expiry,
+            _integrity_checker: integrity_checker,
+            _interest_accrual: interest_accrual,
+            _balance_snapshot: balance_snapshot,
+            _api_key_sync: api_key_sync,
         })
     }
 
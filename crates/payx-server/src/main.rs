@@ -1,13 +1,26 @@
 use anyhow::Result;
-use payx_server::{config::Config, telemetry, App};
+use payx_server::{config::Config, ledger, telemetry, App};
+use sqlx::postgres::PgPoolOptions;
 use tokio::net::TcpListener;
 use tracing::info;
+use uuid::Uuid;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
     let config = Config::from_env()?;
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None | Some("serve") => {}
+        Some("verify-ledger") => return verify_ledger(&config, args.get(1), false).await,
+        Some("rekey-ledger") => return verify_ledger(&config, args.get(1), true).await,
+        Some(other) => anyhow::bail!(
+            "unknown command '{}' (expected 'serve', 'verify-ledger [account-id]' or 'rekey-ledger [account-id]')",
+            other
+        ),
+    }
+
     telemetry::init(&config)?;
 
     let app = App::new(config.clone()).await?;
@@ -24,6 +37,56 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Verifies the ledger hash chain of one account, or of every account, and
+/// exits non-zero if any chain is broken. With `rekey`, chains hashed before
+/// hashes were keyed are rewritten with keyed hashes as they verify.
+async fn verify_ledger(config: &Config, account_id: Option<&String>, rekey: bool) -> Result<()> {
+    ledger::init_hash_key(&config.ledger_hash_key)?;
+    let db = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&config.database_url)
+        .await?;
+
+    let account_ids: Vec<Uuid> = match account_id {
+        Some(id) => vec![id.parse()?],
+        None => {
+            sqlx::query_scalar("SELECT id FROM accounts ORDER BY id")
+                .fetch_all(&db)
+                .await?
+        }
+    };
+
+    let mut broken = 0;
+    for account_id in account_ids {
+        let verification = if rekey {
+            ledger::rekey_chain(&db, account_id).await?
+        } else {
+            ledger::verify_chain(&db, account_id).await?
+        };
+        match verification.first_break {
+            None => println!(
+                "{}: ok ({} entries)",
+                account_id, verification.entries_checked
+            ),
+            Some(b) => {
+                broken += 1;
+                match (b.entry_id, b.seq) {
+                    (Some(entry_id), Some(seq)) => println!(
+                        "{}: BROKEN at entry {} (seq {}): {}",
+                        account_id, entry_id, seq, b.reason
+                    ),
+                    _ => println!("{}: BROKEN: {}", account_id, b.reason),
+                }
+            }
+        }
+    }
+
+    if broken > 0 {
+        anyhow::bail!("{} account ledger(s) failed verification", broken);
+    }
+    Ok(())
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
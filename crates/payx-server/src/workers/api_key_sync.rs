@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::api::middleware::key_cache::{ApiKeyCache, API_KEYS_CHANNEL};
+
+/// Keeps an instance's [`ApiKeyCache`] in step with the database: evicts
+/// keys changed on any instance as their notifications arrive, and writes
+/// the collected `last_used_at` times back in batches. Also prunes the
+/// nonces of signed requests once they can no longer be replayed.
+pub struct ApiKeySync {
+    pool: PgPool,
+    cache: Arc<ApiKeyCache>,
+    flush_interval: Duration,
+    nonce_retention: Duration,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl ApiKeySync {
+    pub fn new(
+        pool: PgPool,
+        cache: Arc<ApiKeyCache>,
+        flush_interval: Duration,
+        nonce_retention: Duration,
+    ) -> Self {
+        Self {
+            pool,
+            cache,
+            flush_interval,
+            nonce_retention,
+            handles: Vec::new(),
+        }
+    }
+
+    pub fn start(&mut self) {
+        let pool = self.pool.clone();
+        let cache = self.cache.clone();
+        self.handles.push(tokio::spawn(async move {
+            loop {
+                if let Err(e) = listen(&pool, &cache).await {
+                    error!(error = %e, "api key listener error");
+                }
+                // Notifications may have been missed while disconnected.
+                cache.clear();
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }));
+
+        let pool = self.pool.clone();
+        let cache = self.cache.clone();
+        let interval = self.flush_interval;
+        let nonce_retention = self.nonce_retention;
+        self.handles.push(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                cache.evict_expired();
+                if let Err(e) = flush_last_used(&pool, &cache).await {
+                    error!(error = %e, "api key last_used_at flush error");
+                }
+                if let Err(e) = prune_nonces(&pool, nonce_retention).await {
+                    error!(error = %e, "request nonce pruning error");
+                }
+            }
+        }));
+    }
+}
+
+async fn listen(pool: &PgPool, cache: &ApiKeyCache) -> sqlx::Result<()> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(API_KEYS_CHANNEL).await?;
+    // Anything cached before the subscription started may be stale.
+    cache.clear();
+
+    loop {
+        match listener.try_recv().await? {
+            Some(notification) => match notification.payload().parse::<Uuid>() {
+                Ok(id) => cache.invalidate(id),
+                Err(_) => {
+                    warn!(
+                        payload = notification.payload(),
+                        "invalid api key notification"
+                    );
+                    cache.clear();
+                }
+            },
+            None => {
+                warn!("api key listener reconnecting");
+                cache.clear();
+            }
+        }
+    }
+}
+
+/// Writes the `last_used_at` times collected since the last flush. On
+/// failure they are kept for the next one.
+pub async fn flush_last_used(pool: &PgPool, cache: &ApiKeyCache) -> anyhow::Result<()> {
+    let last_used = cache.take_last_used();
+    if last_used.is_empty() {
+        return Ok(());
+    }
+
+    let (ids, times): (Vec<Uuid>, Vec<DateTime<Utc>>) = last_used.iter().unzip();
+    let result = sqlx::query(
+        r#"
+        UPDATE api_keys
+        SET last_used_at = GREATEST(api_keys.last_used_at, used.at)
+        FROM UNNEST($1::uuid[], $2::timestamptz[]) AS used(id, at)
+        WHERE api_keys.id = used.id
+        "#,
+    )
+    .bind(&ids)
+    .bind(&times)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        restore(cache, last_used);
+        return Err(e.into());
+    }
+
+    Ok(())
+}
+
+fn restore(cache: &ApiKeyCache, last_used: HashMap<Uuid, DateTime<Utc>>) {
+    for (id, at) in last_used {
+        cache.touch(id, at);
+    }
+}
+
+/// Deletes nonces older than `retention`. Their requests' timestamps are
+/// outside the allowed skew by then, so they would be rejected anyway.
+async fn prune_nonces(pool: &PgPool, retention: Duration) -> sqlx::Result<()> {
+    sqlx::query("DELETE FROM request_nonces WHERE created_at < NOW() - make_interval(secs => $1)")
+        .bind(retention.as_secs_f64())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Announces a change to the key to every instance once `tx` commits.
+pub async fn notify_api_key_changed(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+) -> sqlx::Result<()> {
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(API_KEYS_CHANNEL)
+        .bind(id.to_string())
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
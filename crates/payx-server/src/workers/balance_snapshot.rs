@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::domain::Account;
+use crate::ledger;
+
+pub struct BalanceSnapshotWorker {
+    pool: PgPool,
+    interval: Duration,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BalanceSnapshotWorker {
+    pub fn new(pool: PgPool, interval: Duration) -> Self {
+        Self {
+            pool,
+            interval,
+            handle: None,
+        }
+    }
+
+    pub fn start(&mut self) {
+        let pool = self.pool.clone();
+        let interval = self.interval;
+
+        let handle = tokio::spawn(async move {
+            loop {
+                match run_snapshots(&pool, Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(written) => info!(written, "balance snapshots written"),
+                    Err(e) => error!(error = %e, "balance snapshot error"),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+
+        self.handle = Some(handle);
+    }
+}
+
+/// Writes the closing balance of every day that has ended by `now` in the
+/// account's business timezone, at least [`ledger::DAY_CLOSE_GRACE`] ago, and
+/// has no snapshot yet. Returns the number of snapshots written.
+pub async fn run_snapshots(pool: &PgPool, now: DateTime<Utc>) -> anyhow::Result<usize> {
+    let accounts: Vec<(Uuid, String, Option<NaiveDate>)> = sqlx::query_as(
+        r#"
+        SELECT a.id, b.timezone, s.last_date
+        FROM accounts a
+        JOIN businesses b ON b.id = a.business_id
+        LEFT JOIN LATERAL (
+            SELECT MAX(snapshot_date) AS last_date
+            FROM balance_snapshots
+            WHERE account_id = a.id
+        ) s ON TRUE
+        ORDER BY a.id
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut written = 0;
+    for (account_id, timezone, last_date) in accounts {
+        let tz = timezone.parse::<Tz>().unwrap_or_else(|_| {
+            warn!(%account_id, timezone, "unknown business timezone, using UTC");
+            Tz::UTC
+        });
+        match snapshot_account(pool, account_id, tz, last_date, now).await {
+            Ok(count) => written += count,
+            // One failing account must not block snapshots for everyone else
+            Err(e) => error!(%account_id, error = %e, "balance snapshot failed"),
+        }
+    }
+
+    Ok(written)
+}
+
+async fn snapshot_account(
+    pool: &PgPool,
+    account_id: Uuid,
+    tz: Tz,
+    last_date: Option<NaiveDate>,
+    now: DateTime<Utc>,
+) -> anyhow::Result<usize> {
+    let mut through = ledger::last_closed_day(tz, now);
+    if last_date.is_some_and(|last| last >= through) {
+        return Ok(0);
+    }
+
+    let account: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1")
+        .bind(account_id)
+        .fetch_one(pool)
+        .await?;
+
+    // Closed accounts keep their history but stop changing
+    if let Some(closed_at) = account.closed_at {
+        through = through.min(closed_at.with_timezone(&tz).date_naive());
+    }
+
+    let mut day = match last_date {
+        Some(last) => last + Days::new(1),
+        None => account.created_at.with_timezone(&tz).date_naive(),
+    };
+
+    let mut written = 0;
+    while day <= through {
+        let next = day + Days::new(1);
+        let balance = ledger::balance_at(pool, &account, ledger::day_start(tz, next)).await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO balance_snapshots (account_id, snapshot_date, balance)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (account_id, snapshot_date) DO NOTHING
+            "#,
+        )
+        .bind(account_id)
+        .bind(day)
+        .bind(balance)
+        .execute(pool)
+        .await?;
+
+        written += result.rows_affected() as usize;
+        day = next;
+    }
+
+    Ok(written)
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api::handlers::holds::release_hold;
+use crate::domain::{Hold, HoldStatus};
+
+pub struct HoldExpiryWorker {
+    pool: PgPool,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl HoldExpiryWorker {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, handle: None }
+    }
+
+    pub fn start(&mut self) {
+        let pool = self.pool.clone();
+
+        let handle = tokio::spawn(async move {
+            loop {
+                match expire_due_holds(&pool).await {
+                    Ok(0) => {}
+                    Ok(count) => info!(count, "expired holds"),
+                    Err(e) => error!(error = %e, "hold expiry error"),
+                }
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            }
+        });
+
+        self.handle = Some(handle);
+    }
+}
+
+/// Releases every active hold whose TTL has elapsed. Returns the number of
+/// holds expired in this pass.
+///
+/// Each hold is released in its own transaction, so the worker never holds
+/// one account's lock while waiting for another's. Locking a batch of holds
+/// and then their accounts in expiry order could deadlock with postings,
+/// which lock accounts in ID order.
+pub async fn expire_due_holds(pool: &PgPool) -> anyhow::Result<usize> {
+    let hold_ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM holds
+        WHERE status = 'active'
+        AND expires_at <= NOW()
+        ORDER BY expires_at
+        LIMIT 100
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut expired = 0;
+    for hold_id in hold_ids {
+        let mut tx = pool.begin().await?;
+
+        // Skips holds captured, voided or being worked on since the scan
+        let hold: Option<Hold> = sqlx::query_as(
+            r#"
+            SELECT * FROM holds
+            WHERE id = $1 AND status = 'active' AND expires_at <= NOW()
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(hold_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(hold) = hold {
+            release_hold(&mut tx, &hold, HoldStatus::Expired).await?;
+            tx.commit().await?;
+            expired += 1;
+        }
+    }
+
+    Ok(expired)
+}
@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::Utc;
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::handlers::transactions::enqueue_webhook;
+use crate::domain::{AccountDrift, BalanceChainBreak, IntegrityReport, UnbalancedTransaction};
+use crate::error::Result;
+
+/// Maximum number of violations of each kind included in a report.
+const MAX_VIOLATIONS: i64 = 100;
+
+pub struct IntegrityChecker {
+    pool: PgPool,
+    interval: Duration,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl IntegrityChecker {
+    pub fn new(pool: PgPool, interval: Duration) -> Self {
+        Self {
+            pool,
+            interval,
+            handle: None,
+        }
+    }
+
+    pub fn start(&mut self) {
+        let pool = self.pool.clone();
+        let interval = self.interval;
+
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if let Err(e) = check_and_notify(&pool).await {
+                    error!(error = %e, "integrity check error");
+                }
+            }
+        });
+
+        self.handle = Some(handle);
+    }
+}
+
+/// Runs a full check and enqueues an `integrity.violation` webhook for every
+/// business with at least one violation not reported by an earlier check.
+pub async fn check_and_notify(pool: &PgPool) -> anyhow::Result<IntegrityReport> {
+    let report = run_checks(pool, None).await?;
+
+    if report.is_clean() {
+        info!(
+            accounts = report.accounts_checked,
+            transactions = report.transactions_checked,
+            "ledger integrity check passed"
+        );
+    } else {
+        warn!(
+            account_drifts = report.account_drifts.len(),
+            balance_chain_breaks = report.balance_chain_breaks.len(),
+            unbalanced_transactions = report.unbalanced_transactions.len(),
+            "ledger integrity violations found"
+        );
+    }
+
+    let keys = report.violation_keys();
+    let business_ids: Vec<Uuid> = keys.iter().map(|k| k.0).collect();
+    let kinds: Vec<&str> = keys.iter().map(|k| k.1).collect();
+    let subject_ids: Vec<Uuid> = keys.iter().map(|k| k.2).collect();
+    let truncated_kinds = report.truncated_kinds(MAX_VIOLATIONS as usize);
+
+    let mut tx = pool.begin().await?;
+
+    // Forget violations that are gone, so that a recurrence is reported again.
+    // A kind cut off at MAX_VIOLATIONS was not fully rechecked, so its
+    // missing violations may still be there and are kept.
+    sqlx::query(
+        r#"
+        DELETE FROM reported_integrity_violations
+        WHERE kind <> ALL($4::varchar[])
+        AND (business_id, kind, subject_id) NOT IN (
+            SELECT * FROM UNNEST($1::uuid[], $2::varchar[], $3::uuid[])
+        )
+        "#,
+    )
+    .bind(&business_ids)
+    .bind(&kinds)
+    .bind(&subject_ids)
+    .bind(&truncated_kinds)
+    .execute(&mut *tx)
+    .await?;
+
+    let new: HashSet<(Uuid, String, Uuid)> = sqlx::query_as(
+        r#"
+        INSERT INTO reported_integrity_violations (business_id, kind, subject_id)
+        SELECT * FROM UNNEST($1::uuid[], $2::varchar[], $3::uuid[])
+        ON CONFLICT DO NOTHING
+        RETURNING business_id, kind, subject_id
+        "#,
+    )
+    .bind(&business_ids)
+    .bind(&kinds)
+    .bind(&subject_ids)
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .collect();
+
+    for business_id in report.business_ids() {
+        let unreported = report
+            .for_business(business_id)
+            .filtered(|kind, id| new.contains(&(business_id, kind.to_string(), id)));
+        if unreported.is_clean() {
+            continue;
+        }
+        enqueue_webhook(&mut tx, business_id, "integrity.violation", &unreported).await?;
+    }
+    tx.commit().await?;
+
+    Ok(report)
+}
+
+/// Recomputes balances from the ledger and checks that every transaction
+/// balances. `business_id` limits the check to one business's accounts.
+pub async fn run_checks(pool: &PgPool, business_id: Option<Uuid>) -> Result<IntegrityReport> {
+    let checked_at = Utc::now();
+
+    let accounts_checked: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM accounts WHERE ($1::uuid IS NULL OR business_id = $1)",
+    )
+    .bind(business_id)
+    .fetch_one(pool)
+    .await?;
+
+    let transactions_checked: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(DISTINCT le.transaction_id) FROM ledger_entries le
+        JOIN accounts a ON a.id = le.account_id
+        WHERE ($1::uuid IS NULL OR a.business_id = $1)
+        "#,
+    )
+    .bind(business_id)
+    .fetch_one(pool)
+    .await?;
+
+    let account_drifts: Vec<AccountDrift> = sqlx::query_as(
+        r#"
+        WITH ledger AS (
+            SELECT le.account_id,
+                SUM(CASE WHEN le.entry_type = a.normal_balance THEN le.amount ELSE -le.amount END) AS balance
+            FROM ledger_entries le
+            JOIN accounts a ON a.id = le.account_id
+            WHERE ($1::uuid IS NULL OR a.business_id = $1)
+            GROUP BY le.account_id
+        ),
+        held AS (
+            SELECT h.account_id, SUM(h.amount) AS amount
+            FROM holds h
+            JOIN accounts a ON a.id = h.account_id
+            WHERE h.status = 'active' AND ($1::uuid IS NULL OR a.business_id = $1)
+            GROUP BY h.account_id
+        ),
+        expected AS (
+            SELECT a.id AS account_id, a.business_id,
+                COALESCE(l.balance, 0) AS ledger_balance,
+                a.balance AS stored_balance,
+                COALESCE(l.balance, 0) - COALESCE(h.amount, 0) AS expected_available,
+                a.available_balance AS stored_available
+            FROM accounts a
+            LEFT JOIN ledger l ON l.account_id = a.id
+            LEFT JOIN held h ON h.account_id = a.id
+            WHERE ($1::uuid IS NULL OR a.business_id = $1)
+        )
+        SELECT * FROM expected
+        WHERE ledger_balance <> stored_balance OR expected_available <> stored_available
+        ORDER BY account_id
+        LIMIT $2
+        "#,
+    )
+    .bind(business_id)
+    .bind(MAX_VIOLATIONS)
+    .fetch_all(pool)
+    .await?;
+
+    let balance_chain_breaks: Vec<BalanceChainBreak> = sqlx::query_as(
+        r#"
+        SELECT entry_id, account_id, business_id, expected_balance_after, balance_after
+        FROM (
+            SELECT le.id AS entry_id, le.account_id, a.business_id, le.balance_after, le.seq,
+                COALESCE(LAG(le.balance_after) OVER w, 0)
+                    + CASE WHEN le.entry_type = a.normal_balance THEN le.amount ELSE -le.amount END
+                    AS expected_balance_after
+            FROM ledger_entries le
+            JOIN accounts a ON a.id = le.account_id
+            WHERE ($1::uuid IS NULL OR a.business_id = $1)
+            WINDOW w AS (PARTITION BY le.account_id ORDER BY le.seq)
+        ) chain
+        WHERE expected_balance_after <> balance_after
+        ORDER BY account_id, seq
+        LIMIT $2
+        "#,
+    )
+    .bind(business_id)
+    .bind(MAX_VIOLATIONS)
+    .fetch_all(pool)
+    .await?;
+
+    let unbalanced_transactions: Vec<UnbalancedTransaction> = sqlx::query_as(
+        r#"
+        WITH totals AS (
+            SELECT t.id AS transaction_id,
+                COALESCE(SUM(le.amount) FILTER (WHERE le.entry_type = 'debit'), 0) AS debits,
+                COALESCE(SUM(le.amount) FILTER (WHERE le.entry_type = 'credit'), 0) AS credits,
+                ARRAY(
+                    SELECT DISTINCT a.business_id FROM accounts a
+                    WHERE a.id IN (SELECT account_id FROM ledger_entries WHERE transaction_id = t.id)
+                    OR a.id = t.source_account_id
+                    OR a.id = t.destination_account_id
+                ) AS business_ids
+            FROM transactions t
+            LEFT JOIN ledger_entries le ON le.transaction_id = t.id
+            WHERE t.status = 'completed' AND ($1::uuid IS NULL OR t.business_id = $1)
+            GROUP BY t.id
+        )
+        SELECT * FROM totals
+        WHERE debits <> credits OR debits = 0
+        ORDER BY transaction_id
+        LIMIT $2
+        "#,
+    )
+    .bind(business_id)
+    .bind(MAX_VIOLATIONS)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(IntegrityReport {
+        checked_at,
+        accounts_checked,
+        transactions_checked,
+        account_drifts,
+        balance_chain_breaks,
+        unbalanced_transactions,
+    })
+}
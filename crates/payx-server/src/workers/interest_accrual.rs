@@ -0,0 +1,339 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::handlers::transactions::{enqueue_webhook, ensure_can_post, ensure_funds};
+use crate::domain::{
+    Account, AccountInterest, EntryType, InterestAccrual, Transaction, TransactionStatus,
+    TransactionType, ACCRUAL_SCALE, INTEREST_DAY_COUNT,
+};
+use crate::ledger;
+
+pub struct InterestAccrualWorker {
+    pool: PgPool,
+    interval: Duration,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl InterestAccrualWorker {
+    pub fn new(pool: PgPool, interval: Duration) -> Self {
+        Self {
+            pool,
+            interval,
+            handle: None,
+        }
+    }
+
+    pub fn start(&mut self) {
+        let pool = self.pool.clone();
+        let interval = self.interval;
+
+        let handle = tokio::spawn(async move {
+            loop {
+                match run_interest(&pool, Utc::now()).await {
+                    Ok(run) if run.accrued == 0 && run.posted == 0 => {}
+                    Ok(run) => info!(accrued = run.accrued, posted = run.posted, "interest run"),
+                    Err(e) => error!(error = %e, "interest accrual error"),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+
+        self.handle = Some(handle);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InterestRun {
+    /// Daily accruals written.
+    pub accrued: usize,
+    /// Interest transactions posted.
+    pub posted: usize,
+}
+
+/// Accrues every day that has ended by `now` in the account's business
+/// timezone, at least [`ledger::DAY_CLOSE_GRACE`] ago, and is not accrued
+/// yet, then posts the accruals of every month whose days have all ended by
+/// then.
+///
+/// Frozen and closed accounts are skipped, and so are accounts whose expense
+/// account is frozen or closed when posting. Accrual reads closing balances
+/// from the ledger, so a frozen account catches up once it is unfrozen, and
+/// its accruals are posted then.
+pub async fn run_interest(pool: &PgPool, now: DateTime<Utc>) -> anyhow::Result<InterestRun> {
+    let mut run = InterestRun::default();
+
+    let due: Vec<(Uuid, String, NaiveDate)> = sqlx::query_as(
+        r#"
+        SELECT i.account_id, b.timezone, i.accrued_through
+        FROM account_interest i
+        JOIN accounts a ON a.id = i.account_id
+        JOIN businesses b ON b.id = a.business_id
+        WHERE a.status = 'active'
+        ORDER BY i.account_id
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for (account_id, timezone, accrued_through) in due {
+        let tz = business_tz(account_id, &timezone);
+        let through = ledger::last_closed_day(tz, now);
+        if accrued_through < through {
+            match accrue_account(pool, account_id, tz, through).await {
+                Ok(accrued) => run.accrued += accrued,
+                // One failing account must not block accrual for everyone else
+                Err(e) => error!(%account_id, error = %e, "interest accrual failed"),
+            }
+        }
+    }
+
+    let unposted: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT ia.account_id, b.timezone
+        FROM interest_accruals ia
+        JOIN account_interest i ON i.account_id = ia.account_id
+        JOIN accounts a ON a.id = ia.account_id
+        JOIN accounts e ON e.id = i.expense_account_id
+        JOIN businesses b ON b.id = a.business_id
+        WHERE ia.posted_at IS NULL AND a.status = 'active' AND e.status = 'active'
+        ORDER BY ia.account_id
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for (account_id, timezone) in unposted {
+        // A month is posted once its last day has accrued
+        let tz = business_tz(account_id, &timezone);
+        let month_start = (ledger::last_closed_day(tz, now) + Days::new(1))
+            .with_day(1)
+            .expect("first of month is valid");
+
+        match post_account(pool, account_id, month_start).await {
+            Ok(true) => run.posted += 1,
+            Ok(false) => {}
+            // One failing account must not block posting for everyone else
+            Err(e) => error!(%account_id, error = %e, "interest posting failed"),
+        }
+    }
+
+    Ok(run)
+}
+
+fn business_tz(account_id: Uuid, timezone: &str) -> Tz {
+    timezone.parse::<Tz>().unwrap_or_else(|_| {
+        warn!(%account_id, timezone, "unknown business timezone, using UTC");
+        Tz::UTC
+    })
+}
+
+/// Writes one accrual per day from the account's last accrued day through
+/// `through`, on each day's closing balance in `tz`. Days with no positive
+/// balance accrue nothing.
+async fn accrue_account(
+    pool: &PgPool,
+    account_id: Uuid,
+    tz: Tz,
+    through: NaiveDate,
+) -> anyhow::Result<usize> {
+    let mut tx = pool.begin().await?;
+
+    // Another instance may be accruing this account already
+    let interest: Option<AccountInterest> = sqlx::query_as(
+        r#"
+        SELECT * FROM account_interest
+        WHERE account_id = $1 AND accrued_through < $2
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(account_id)
+    .bind(through)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(interest) = interest else {
+        return Ok(0);
+    };
+
+    let account: Account = sqlx::query_as("SELECT * FROM accounts WHERE id = $1")
+        .bind(account_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let mut accrued = 0;
+    let mut day = interest.accrued_through + Days::new(1);
+    while day <= through {
+        let day_end = ledger::day_start(tz, day + Days::new(1));
+        let balance = ledger::balance_at(pool, &account, day_end).await?;
+
+        let amount = (balance * interest.annual_rate / Decimal::from(INTEREST_DAY_COUNT))
+            .round_dp_with_strategy(ACCRUAL_SCALE, RoundingStrategy::MidpointNearestEven);
+
+        if amount > Decimal::ZERO {
+            sqlx::query(
+                r#"
+                INSERT INTO interest_accruals (account_id, accrual_date, balance, annual_rate, amount)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (account_id, accrual_date) DO NOTHING
+                "#,
+            )
+            .bind(account_id)
+            .bind(day)
+            .bind(balance)
+            .bind(interest.annual_rate)
+            .bind(amount)
+            .execute(&mut *tx)
+            .await?;
+            accrued += 1;
+        }
+
+        day = day + Days::new(1);
+    }
+
+    sqlx::query("UPDATE account_interest SET accrued_through = $1 WHERE account_id = $2")
+        .bind(through)
+        .bind(account_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(accrued)
+}
+
+/// Posts the account's unposted accruals dated before `month_start` as one
+/// `interest` transaction from its expense account, rounded to the ledger's scale with
+/// banker's rounding. Returns whether a transaction was posted.
+async fn post_account(
+    pool: &PgPool,
+    account_id: Uuid,
+    month_start: NaiveDate,
+) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    // Serializes posting of the same account across instances
+    let interest: AccountInterest =
+        sqlx::query_as("SELECT * FROM account_interest WHERE account_id = $1 FOR UPDATE")
+            .bind(account_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    let accruals: Vec<InterestAccrual> = sqlx::query_as(
+        r#"
+        SELECT * FROM interest_accruals
+        WHERE account_id = $1 AND posted_at IS NULL AND accrual_date < $2
+        ORDER BY accrual_date
+        "#,
+    )
+    .bind(account_id)
+    .bind(month_start)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let (Some(first), Some(last)) = (accruals.first(), accruals.last()) else {
+        return Ok(false);
+    };
+    let (from, to) = (first.accrual_date, last.accrual_date);
+    let accrual_ids: Vec<Uuid> = accruals.iter().map(|a| a.id).collect();
+
+    let amount = accruals
+        .iter()
+        .map(|a| a.amount)
+        .sum::<Decimal>()
+        .round_dp_with_strategy(ledger::LEDGER_SCALE, RoundingStrategy::MidpointNearestEven);
+    let now = Utc::now();
+
+    if amount.is_zero() {
+        sqlx::query("UPDATE interest_accruals SET posted_at = $1 WHERE id = ANY($2)")
+            .bind(now)
+            .bind(&accrual_ids)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        return Ok(false);
+    }
+
+    let expense_id = interest.expense_account_id;
+    let mut accounts = ledger::lock_accounts(&mut tx, &[account_id, expense_id]).await?;
+    let account = accounts
+        .remove(&account_id)
+        .ok_or_else(|| anyhow::anyhow!("account not found: {}", account_id))?;
+    let expense = accounts
+        .remove(&expense_id)
+        .ok_or_else(|| anyhow::anyhow!("account not found: {}", expense_id))?;
+
+    ensure_can_post(&account, EntryType::Credit)?;
+    ensure_can_post(&expense, EntryType::Debit)?;
+    ensure_funds(&account, EntryType::Credit, amount)?;
+    ensure_funds(&expense, EntryType::Debit, amount)?;
+
+    let txn_id = Uuid::new_v4();
+    let transaction: Transaction = sqlx::query_as(
+        r#"
+        INSERT INTO transactions (id, business_id, type, status, source_account_id, destination_account_id, amount, currency, description, metadata, created_at, completed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11)
+        RETURNING *
+        "#,
+    )
+    .bind(txn_id)
+    .bind(account.business_id)
+    .bind(TransactionType::Interest)
+    .bind(TransactionStatus::Completed)
+    .bind(expense_id)
+    .bind(account_id)
+    .bind(amount)
+    .bind(&account.currency)
+    .bind(format!("Interest {} to {}", from, to))
+    .bind(serde_json::json!({ "interest": { "from": from, "to": to } }))
+    .bind(now)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    for (posted, entry_type) in [(&account, EntryType::Credit), (&expense, EntryType::Debit)] {
+        let delta = posted.balance_delta(entry_type, amount);
+
+        sqlx::query("UPDATE accounts SET balance = $1, available_balance = $2, version = version + 1, updated_at = $3 WHERE id = $4")
+            .bind(posted.balance + delta)
+            .bind(posted.available_balance + delta)
+            .bind(now)
+            .bind(posted.id)
+            .execute(&mut *tx)
+            .await?;
+
+        ledger::post_entry(
+            &mut tx,
+            txn_id,
+            posted.id,
+            entry_type,
+            amount,
+            posted.balance + delta,
+            now,
+        )
+        .await?;
+    }
+
+    sqlx::query(
+        "UPDATE interest_accruals SET transaction_id = $1, posted_at = $2 WHERE id = ANY($3)",
+    )
+    .bind(txn_id)
+    .bind(now)
+    .bind(&accrual_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    enqueue_webhook(
+        &mut tx,
+        account.business_id,
+        "transaction.completed",
+        &transaction,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
@@ -1 +1,6 @@
+pub mod api_key_sync;
+pub mod balance_snapshot;
+pub mod hold_expiry;
+pub mod integrity_checker;
+pub mod interest_accrual;
 pub mod webhook_processor;
@@ -1,8 +1,12 @@
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
 use axum::Router;
+use payx_server::api::middleware::key_cache::ApiKeyCache;
 use payx_server::config::Config;
-use payx_server::App;
+use payx_server::domain::{
+    request_signing_string, seal_signing_secret, sign_payload, ApiKey, LedgerEntry, GENESIS_HASH,
+};
+use payx_server::{ledger, App};
 use serde_json::{json, Value};
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -52,18 +56,35 @@ async fn get_test_db() -> (PgPool, String) {
     (pool, database_url)
 }
 
-async fn setup() -> (Router, PgPool) {
-    let (_pool, database_url) = get_test_db().await;
+const TEST_LEDGER_HASH_KEY: &str = "test-ledger-hash-key-0123456789abcdef";
+const TEST_SECRET_ENCRYPTION_KEY: [u8; 32] = [7; 32];
 
-    let config = Config {
+fn test_config(database_url: String) -> Config {
+    Config {
         database_url,
         bind_address: "0.0.0.0:8080".to_string(),
         db_max_connections: 5,
         otlp_endpoint: None,
         rate_limit_per_minute: 1000,
-    };
+        hold_ttl_seconds: 604800,
+        integrity_check_interval_seconds: 3600,
+        batch_max_items: 1000,
+        interest_accrual_interval_seconds: 3600,
+        balance_snapshot_interval_seconds: 3600,
+        api_key_cache_ttl_seconds: 60,
+        api_key_last_used_flush_seconds: 3600,
+        request_signature_max_skew_seconds: 300,
+        ledger_hash_key: TEST_LEDGER_HASH_KEY.to_string(),
+        secret_encryption_key: TEST_SECRET_ENCRYPTION_KEY,
+    }
+}
+
+async fn setup() -> (Router, PgPool) {
+    let (_pool, database_url) = get_test_db().await;
 
-    let app = App::new(config).await.expect("Failed to create app");
+    let app = App::new(test_config(database_url))
+        .await
+        .expect("Failed to create app");
     let pool = app.db().clone();
 
     sqlx::query("TRUNCATE businesses, accounts, transactions, ledger_entries, api_keys, webhook_outbox, rate_limit_windows CASCADE")
@@ -170,6 +191,53 @@ async fn get_balance(router: &Router, api_key: &str, account_id: &str) -> String
     json["balance"].as_str().unwrap().to_string()
 }
 
+async fn send_json(
+    router: &Router,
+    api_key: &str,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", api_key));
+
+    let body = match body {
+        Some(json) => {
+            builder = builder.header("content-type", "application/json");
+            Body::from(json.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let res = router
+        .clone()
+        .oneshot(builder.body(body).unwrap())
+        .await
+        .unwrap();
+
+    let status = res.status();
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    (status, json)
+}
+
+async fn get_account(router: &Router, api_key: &str, account_id: &str) -> Value {
+    let (status, json) = send_json(
+        router,
+        api_key,
+        "GET",
+        &format!("/v1/accounts/{}", account_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    json
+}
+
 // =============================================================================
 // TRANSFER TESTS
 // =============================================================================
@@ -343,6 +411,91 @@ async fn test_idempotency() {
     assert_eq!(balance, "600.0000");
 }
 
+async fn post_idempotent(
+    router: &Router,
+    api_key: &str,
+    uri: &str,
+    idempotency_key: &str,
+    body: Value,
+) -> (StatusCode, Value) {
+    let res = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("authorization", format!("Bearer {}", api_key))
+                .header("content-type", "application/json")
+                .header("idempotency-key", idempotency_key)
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = res.status();
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_idempotency_keys_are_scoped_to_business() {
+    let (router, _pool) = setup().await;
+
+    let mut transaction_ids = Vec::new();
+    let mut hold_ids = Vec::new();
+    for _ in 0..2 {
+        let (business_id, api_key) = create_business(&router).await;
+        let account_id = create_account(&router, &api_key, &business_id, "500.00").await;
+        let credit = json!({
+            "type": "credit",
+            "destination_account_id": account_id,
+            "amount": "100.00",
+            "currency": "USD"
+        });
+        let hold = json!({
+            "source_account_id": account_id,
+            "amount": "50.00",
+            "currency": "USD"
+        });
+
+        let (status, txn) = post_idempotent(
+            &router,
+            &api_key,
+            "/v1/transactions",
+            "shared-key",
+            credit.clone(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, again) =
+            post_idempotent(&router, &api_key, "/v1/transactions", "shared-key", credit).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(again["id"], txn["id"]);
+        transaction_ids.push(txn["id"].clone());
+
+        let (status, placed) = post_idempotent(
+            &router,
+            &api_key,
+            "/v1/holds",
+            "shared-hold-key",
+            hold.clone(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, again) =
+            post_idempotent(&router, &api_key, "/v1/holds", "shared-hold-key", hold).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(again["id"], placed["id"]);
+        hold_ids.push(placed["id"].clone());
+    }
+
+    assert_ne!(transaction_ids[0], transaction_ids[1]);
+    assert_ne!(hold_ids[0], hold_ids[1]);
+}
+
 // =============================================================================
 // ERROR HANDLING TESTS
 // =============================================================================
@@ -553,6 +706,707 @@ async fn test_malformed_authorization_header() {
     assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
 }
 
+#[tokio::test]
+async fn test_tenant_isolation() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let (other_business_id, other_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let other_account_id = create_account(&router, &other_key, &other_business_id, "100.00").await;
+    let txn = transfer(
+        &router,
+        &api_key,
+        &account_id,
+        &create_account(&router, &api_key, &business_id, "0.00").await,
+        "10.00",
+    )
+    .await;
+
+    for uri in [
+        format!("/v1/businesses/{}", business_id),
+        format!("/v1/accounts/{}", account_id),
+        format!("/v1/accounts/{}/ledger", account_id),
+        format!("/v1/accounts/{}/balance", account_id),
+        format!("/v1/transactions/{}", txn["id"].as_str().unwrap()),
+        format!("/v1/transactions?account_id={}", account_id),
+    ] {
+        let (status, _) = send_json(&router, &other_key, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "GET {}", uri);
+    }
+
+    let (status, _) = send_json(
+        &router,
+        &other_key,
+        "PUT",
+        &format!("/v1/businesses/{}", business_id),
+        Some(json!({ "name": "Taken over" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_json(
+        &router,
+        &other_key,
+        "POST",
+        &format!("/v1/accounts/{}/freeze", account_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_json(
+        &router,
+        &other_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({ "business_id": business_id, "currency": "USD" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_json(
+        &router,
+        &other_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "transfer",
+            "source_account_id": account_id,
+            "destination_account_id": other_account_id,
+            "amount": "50.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_json(
+        &router,
+        &other_key,
+        "POST",
+        &format!("/v1/transactions/{}/reverse", txn["id"].as_str().unwrap()),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, businesses) = send_json(&router, &other_key, "GET", "/v1/businesses", None).await;
+    let businesses = businesses.as_array().unwrap();
+    assert_eq!(businesses.len(), 1);
+    assert_eq!(businesses[0]["id"], other_business_id.as_str());
+
+    let (_, accounts) = send_json(&router, &other_key, "GET", "/v1/accounts", None).await;
+    assert!(accounts
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|a| a["business_id"] == other_business_id.as_str()));
+
+    let (_, transactions) = send_json(&router, &other_key, "GET", "/v1/transactions", None).await;
+    assert!(transactions
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|t| t["id"] != txn["id"]));
+
+    assert_eq!(get_balance(&router, &api_key, &account_id).await, "90.0000");
+}
+
+#[tokio::test]
+async fn test_api_key_create_rotate_revoke() {
+    let (router, _pool) = setup().await;
+
+    let (_business_id, api_key) = create_business(&router).await;
+    let (_, other_key) = create_business(&router).await;
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/api_keys",
+        Some(json!({ "name": "reporting", "expires_at": "2000-01-01T00:00:00Z" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, created) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/api_keys",
+        Some(json!({ "name": "reporting" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["name"], "reporting");
+    let reporting_id = created["id"].as_str().unwrap().to_string();
+    let reporting_key = created["key"].as_str().unwrap().to_string();
+
+    let (status, keys) = send_json(&router, &reporting_key, "GET", "/v1/api_keys", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let keys = keys.as_array().unwrap();
+    assert_eq!(keys.len(), 2);
+    assert!(keys.iter().all(|k| k.get("key").is_none()));
+    assert!(keys.iter().all(|k| k.get("key_hash").is_none()));
+    let listed = keys
+        .iter()
+        .find(|k| k["id"] == reporting_id.as_str())
+        .unwrap();
+    assert_eq!(listed["prefix"], &reporting_key[..12]);
+    assert!(listed["last_used_at"].is_string());
+
+    // Another business can neither see nor touch the key.
+    let (_, other_keys) = send_json(&router, &other_key, "GET", "/v1/api_keys", None).await;
+    assert_eq!(other_keys.as_array().unwrap().len(), 1);
+    let (status, _) = send_json(
+        &router,
+        &other_key,
+        "POST",
+        &format!("/v1/api_keys/{}/revoke", reporting_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // With an overlap, both the old and the new key work.
+    let (status, rotated) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/api_keys/{}/rotate", reporting_id),
+        Some(json!({ "overlap_seconds": 3600 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(rotated["api_key"]["name"], "reporting");
+    assert!(rotated["previous_key_expires_at"].is_string());
+    let rotated_key = rotated["api_key"]["key"].as_str().unwrap().to_string();
+    let rotated_id = rotated["api_key"]["id"].as_str().unwrap().to_string();
+
+    for key in [&reporting_key, &rotated_key] {
+        let (status, _) = send_json(&router, key, "GET", "/v1/api_keys", None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // Without one, the old key stops working at once.
+    let (status, rotated_again) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/api_keys/{}/rotate", rotated_id),
+        Some(json!({ "overlap_seconds": 0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = send_json(&router, &rotated_key, "GET", "/v1/api_keys", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let latest_key = rotated_again["api_key"]["key"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let latest_id = rotated_again["api_key"]["id"].as_str().unwrap().to_string();
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/api_keys/{}/revoke", latest_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_json(&router, &latest_key, "GET", "/v1/api_keys", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // The overlap window of the first rotation still lets the original
+    // reporting key in, so the signup key is not the last one yet.
+    let (_, keys) = send_json(&router, &api_key, "GET", "/v1/api_keys", None).await;
+    assert_eq!(keys.as_array().unwrap().len(), 3);
+
+    let signup_id = keys
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|k| k["name"].is_null())
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let (status, _) = send_json(
+        &router,
+        &reporting_key,
+        "POST",
+        &format!("/v1/api_keys/{}/revoke", signup_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, error) = send_json(
+        &router,
+        &reporting_key,
+        "POST",
+        &format!("/v1/api_keys/{}/revoke", reporting_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["error"]["code"], "validation_error");
+}
+
+#[tokio::test]
+async fn test_api_key_scopes() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    let (_, keys) = send_json(&router, &api_key, "GET", "/v1/api_keys", None).await;
+    assert!(keys[0]["scopes"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("transactions:write")));
+
+    let (status, read_only) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/api_keys",
+        Some(json!({
+            "name": "reporting",
+            "scopes": ["transactions:read", "accounts:read"]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(
+        read_only["scopes"],
+        json!(["accounts:read", "transactions:read"])
+    );
+    let read_only = read_only["key"].as_str().unwrap().to_string();
+
+    assert_eq!(get_balance(&router, &read_only, &source).await, "100.0000");
+    let (status, _) = send_json(&router, &read_only, "GET", "/v1/transactions", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, error) = send_json(
+        &router,
+        &read_only,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "transfer",
+            "source_account_id": source,
+            "destination_account_id": dest,
+            "amount": "10.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(error["error"]["code"], "insufficient_scope");
+    assert_eq!(
+        error["error"]["details"]["required_scope"],
+        "transactions:write"
+    );
+
+    let (status, _) = send_json(
+        &router,
+        &read_only,
+        "POST",
+        &format!("/v1/accounts/{}/freeze", source),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send_json(&router, &read_only, "GET", "/v1/api_keys", None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // A key manager cannot issue scopes it does not hold itself.
+    let (_, manager) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/api_keys",
+        Some(json!({ "scopes": ["api_keys:manage", "accounts:read"] })),
+    )
+    .await;
+    let manager = manager["key"].as_str().unwrap().to_string();
+
+    let (status, error) = send_json(
+        &router,
+        &manager,
+        "POST",
+        "/v1/api_keys",
+        Some(json!({ "scopes": ["transactions:write"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(
+        error["error"]["details"]["required_scope"],
+        "transactions:write"
+    );
+
+    // ... nor revoke a key with scopes it does not hold
+    let revoke = |id: &str| format!("/v1/api_keys/{}/revoke", id);
+    let (status, error) = send_json(
+        &router,
+        &manager,
+        "POST",
+        &revoke(keys[0]["id"].as_str().unwrap()),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(error["error"]["code"], "insufficient_scope");
+    assert_eq!(get_balance(&router, &api_key, &source).await, "100.0000");
+
+    let (_, narrow) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/api_keys",
+        Some(json!({ "scopes": ["accounts:read"] })),
+    )
+    .await;
+    let (status, _) = send_json(
+        &router,
+        &manager,
+        "POST",
+        &revoke(narrow["id"].as_str().unwrap()),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/api_keys",
+        Some(json!({ "scopes": [] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let balance = get_balance(&router, &api_key, &dest).await;
+    assert!(balance == "0.0000" || balance == "0");
+}
+
+#[test]
+fn test_api_key_cache_skips_insert_raced_by_invalidation() {
+    let cache = ApiKeyCache::new(std::time::Duration::from_secs(60));
+    let (api_key, generated) = ApiKey::generate(
+        uuid::Uuid::new_v4(),
+        None,
+        Vec::new(),
+        None,
+        &TEST_SECRET_ENCRYPTION_KEY,
+    );
+
+    // A lookup that started before the key was revoked must not cache it.
+    let generation = cache.generation();
+    cache.invalidate(api_key.id);
+    cache.insert(&generated.key, api_key.clone(), generation);
+    assert!(cache.get(&generated.key).is_none());
+
+    let generation = cache.generation();
+    cache.clear();
+    cache.insert(&generated.key, api_key.clone(), generation);
+    assert!(cache.get(&generated.key).is_none());
+
+    let generation = cache.generation();
+    cache.insert(&generated.key, api_key, generation);
+    assert!(cache.get(&generated.key).is_some());
+}
+
+#[tokio::test]
+async fn test_api_key_revocation_reaches_other_instances() {
+    let (router, pool) = setup().await;
+    let (_, database_url) = get_test_db().await;
+    let replica = App::new(test_config(database_url))
+        .await
+        .expect("Failed to create app")
+        .router();
+
+    let (_, api_key) = create_business(&router).await;
+    let (_, created) = send_json(&router, &api_key, "POST", "/v1/api_keys", Some(json!({}))).await;
+    let key_id = created["id"].as_str().unwrap().to_string();
+    let key = created["key"].as_str().unwrap().to_string();
+
+    // Give the replica's listener time to subscribe, then let it cache the key.
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    for _ in 0..3 {
+        let (status, _) = send_json(&replica, &key, "GET", "/v1/businesses", None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // Uses are written back in batches, not per request.
+    let last_used: Option<chrono::DateTime<chrono::Utc>> =
+        sqlx::query_scalar("SELECT last_used_at FROM api_keys WHERE id = $1::uuid")
+            .bind(&key_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(last_used.is_none());
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/api_keys/{}/revoke", key_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let mut status = StatusCode::OK;
+    for _ in 0..50 {
+        (status, _) = send_json(&replica, &key, "GET", "/v1/businesses", None).await;
+        if status == StatusCode::UNAUTHORIZED {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+/// Sends a request signed with `key`, an API key ID and its signing secret.
+async fn send_signed(
+    router: &Router,
+    (key_id, secret): (&str, &str),
+    method: &str,
+    uri: &str,
+    body: &str,
+    timestamp: i64,
+    nonce: &str,
+) -> (StatusCode, Value) {
+    let signing_string = request_signing_string(method, uri, timestamp, nonce, body.as_bytes());
+    let res = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("x-payx-key-id", key_id)
+                .header("x-payx-timestamp", timestamp.to_string())
+                .header("x-payx-nonce", nonce)
+                .header(
+                    "x-payx-signature",
+                    sign_payload(signing_string.as_bytes(), secret),
+                )
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = res.status();
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_signed_requests() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let (_, created) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/api_keys",
+        Some(json!({ "name": "edge" })),
+    )
+    .await;
+    let key_id = created["id"].as_str().unwrap();
+    let secret = created["signing_secret"].as_str().unwrap();
+    let now = chrono::Utc::now().timestamp();
+
+    let uri = format!("/v1/accounts/{}?include=balance", account_id);
+    let (status, account) =
+        send_signed(&router, (key_id, secret), "GET", &uri, "", now, "nonce-1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(account["id"], account_id.as_str());
+
+    let body = json!({
+        "type": "credit",
+        "destination_account_id": account_id,
+        "amount": "25.00",
+        "currency": "USD"
+    })
+    .to_string();
+    let (status, _) = send_signed(
+        &router,
+        (key_id, secret),
+        "POST",
+        "/v1/transactions",
+        &body,
+        now,
+        "nonce-2",
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(
+        get_balance(&router, &api_key, &account_id).await,
+        "125.0000"
+    );
+
+    // Replaying the exact request is rejected.
+    let (status, error) = send_signed(
+        &router,
+        (key_id, secret),
+        "POST",
+        "/v1/transactions",
+        &body,
+        now,
+        "nonce-2",
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(error["error"]["code"], "invalid_signature");
+
+    let (status, _) = send_signed(
+        &router,
+        (key_id, secret),
+        "GET",
+        &uri,
+        "",
+        now - 3600,
+        "nonce-3",
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send_signed(
+        &router,
+        (key_id, "not-the-secret"),
+        "GET",
+        &uri,
+        "",
+        now,
+        "nonce-4",
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // A body other than the one signed fails verification.
+    let signing_string =
+        request_signing_string("POST", "/v1/transactions", now, "nonce-5", body.as_bytes());
+    let res = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/transactions")
+                .header("content-type", "application/json")
+                .header("x-payx-key-id", key_id)
+                .header("x-payx-timestamp", now.to_string())
+                .header("x-payx-nonce", "nonce-5")
+                .header(
+                    "x-payx-signature",
+                    sign_payload(signing_string.as_bytes(), secret),
+                )
+                .body(Body::from(body.replace("25.00", "2500.00")))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    assert_eq!(
+        get_balance(&router, &api_key, &account_id).await,
+        "125.0000"
+    );
+}
+
+#[tokio::test]
+async fn test_signing_secrets_are_encrypted_at_rest() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let (_, created) = send_json(&router, &api_key, "POST", "/v1/api_keys", Some(json!({}))).await;
+    let key_id = created["id"].as_str().unwrap();
+    let secret = created["signing_secret"].as_str().unwrap();
+    let uri = format!("/v1/accounts/{}", account_id);
+
+    let stored: String =
+        sqlx::query_scalar("SELECT signing_secret FROM api_keys WHERE id = $1::uuid")
+            .bind(key_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(stored.starts_with("v1:"));
+    assert!(!stored.contains(secret));
+
+    // A secret stored before encryption still verifies, and is sealed when
+    // the server next starts.
+    sqlx::query("UPDATE api_keys SET signing_secret = $1 WHERE id = $2::uuid")
+        .bind(secret)
+        .bind(key_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let now = chrono::Utc::now().timestamp();
+    let (status, _) = send_signed(&router, (key_id, secret), "GET", &uri, "", now, "n-1").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, database_url) = get_test_db().await;
+    let restarted = App::new(test_config(database_url)).await.unwrap();
+    let resealed: String =
+        sqlx::query_scalar("SELECT signing_secret FROM api_keys WHERE id = $1::uuid")
+            .bind(key_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(resealed.starts_with("v1:"));
+    let (status, _) = send_signed(
+        &restarted.router(),
+        (key_id, secret),
+        "GET",
+        &uri,
+        "",
+        now,
+        "n-2",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // A sealed secret copied onto another key's row does not open.
+    let other = uuid::Uuid::new_v4();
+    let mut copied: ApiKey = sqlx::query_as("SELECT * FROM api_keys WHERE id = $1::uuid")
+        .bind(key_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    copied.id = other;
+    assert!(copied
+        .open_signing_secret(&TEST_SECRET_ENCRYPTION_KEY)
+        .is_err());
+
+    copied.signing_secret = Some(seal_signing_secret(
+        &TEST_SECRET_ENCRYPTION_KEY,
+        other,
+        secret,
+    ));
+    assert_eq!(
+        copied
+            .open_signing_secret(&TEST_SECRET_ENCRYPTION_KEY)
+            .unwrap()
+            .as_deref(),
+        Some(secret)
+    );
+    assert!(copied.open_signing_secret(&[8; 32]).is_err());
+}
+
 // =============================================================================
 // BUSINESS TESTS
 // =============================================================================
@@ -740,6 +1594,70 @@ async fn test_create_account() {
     assert_eq!(json["balance"].as_str().unwrap(), "1000.0000");
 }
 
+#[tokio::test]
+async fn test_opening_balance_posted_to_ledger() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "250.00").await;
+
+    let uri = format!("/v1/accounts/{}/transactions", account_id);
+    let (status, transactions) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let transactions = transactions.as_array().unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0]["type"], "opening");
+    assert_eq!(transactions[0]["status"], "completed");
+    assert_eq!(transactions[0]["amount"], "250.0000");
+
+    let uri = format!("/v1/accounts/{}/ledger", account_id);
+    let (_, ledger) = send_json(&router, &api_key, "GET", &uri, None).await;
+    let entries = ledger.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["entry_type"], "credit");
+    assert_eq!(entries[0]["balance_after"], "250.0000");
+
+    let settlement_balance: rust_decimal::Decimal = sqlx::query_scalar(
+        "SELECT balance FROM accounts WHERE business_id = $1::uuid AND is_settlement",
+    )
+    .bind(&business_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(settlement_balance.to_string(), "250.0000");
+
+    let events: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM webhook_outbox WHERE business_id = $1::uuid AND event_type = 'transaction.completed'",
+    )
+    .bind(&business_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(events, 1);
+
+    let (_, report) = send_json(&router, &api_key, "GET", "/v1/integrity/report", None).await;
+    assert!(report["account_drifts"].as_array().unwrap().is_empty());
+    assert!(report["unbalanced_transactions"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "opening",
+            "destination_account_id": account_id,
+            "amount": "10.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn test_list_accounts() {
     let (router, _pool) = setup().await;
@@ -769,6 +1687,27 @@ async fn test_list_accounts() {
     let json: Value = serde_json::from_slice(&body).unwrap();
     let accounts = json.as_array().unwrap();
     assert_eq!(accounts.len(), 2);
+
+    // Opening balances create the business's settlement account, which is
+    // only listed on request
+    let (status, json) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        "/v1/accounts?include_settlement=true",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let accounts = json.as_array().unwrap();
+    assert_eq!(accounts.len(), 3);
+    assert_eq!(
+        accounts
+            .iter()
+            .filter(|a| a["is_settlement"] == true)
+            .count(),
+        1
+    );
 }
 
 // =============================================================================
@@ -1141,3 +2080,3117 @@ async fn test_concurrent_debits_respect_balance() {
     // Balance can be "0.0000" or "0" depending on decimal formatting
     assert!(balance == "0.0000" || balance == "0");
 }
+
+// =============================================================================
+// HOLD TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_hold_partial_capture() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    let (status, hold) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/holds",
+        Some(json!({
+            "source_account_id": source_id,
+            "destination_account_id": dest_id,
+            "amount": "60.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(hold["status"], "active");
+
+    let source = get_account(&router, &api_key, &source_id).await;
+    assert_eq!(source["balance"], "100.0000");
+    assert_eq!(source["available_balance"], "40.0000");
+
+    let (status, txn) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!(
+            "/v1/transactions/{}",
+            hold["transaction_id"].as_str().unwrap()
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(txn["status"], "pending");
+
+    let (status, captured) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/holds/{}/capture", hold["id"].as_str().unwrap()),
+        Some(json!({ "amount": "45.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(captured["status"], "captured");
+    assert_eq!(captured["captured_amount"], "45.0000");
+
+    let source = get_account(&router, &api_key, &source_id).await;
+    assert_eq!(source["balance"], "55.0000");
+    assert_eq!(source["available_balance"], "55.0000");
+
+    let dest = get_account(&router, &api_key, &dest_id).await;
+    assert_eq!(dest["balance"], "45.0000");
+    assert_eq!(dest["available_balance"], "45.0000");
+
+    let (_, txn) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!(
+            "/v1/transactions/{}",
+            hold["transaction_id"].as_str().unwrap()
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(txn["status"], "completed");
+    assert_eq!(txn["amount"], "45.0000");
+}
+
+#[tokio::test]
+async fn test_hold_reserves_available_balance() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "100.00").await;
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/holds",
+        Some(json!({
+            "source_account_id": account_id,
+            "amount": "80.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, body) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "debit",
+            "source_account_id": account_id,
+            "amount": "30.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"]["code"], "insufficient_funds");
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "credit",
+            "destination_account_id": account_id,
+            "amount": "10.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let account = get_account(&router, &api_key, &account_id).await;
+    assert_eq!(account["balance"], "110.0000");
+    assert_eq!(account["available_balance"], "30.0000");
+}
+
+#[tokio::test]
+async fn test_hold_void_releases_funds() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "100.00").await;
+
+    let (_, hold) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/holds",
+        Some(json!({
+            "source_account_id": account_id,
+            "amount": "30.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    let hold_id = hold["id"].as_str().unwrap();
+
+    let (status, voided) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/holds/{}/void", hold_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(voided["status"], "voided");
+
+    let account = get_account(&router, &api_key, &account_id).await;
+    assert_eq!(account["balance"], "100.0000");
+    assert_eq!(account["available_balance"], "100.0000");
+
+    let (status, body) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/holds/{}/capture", hold_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["error"]["code"], "hold_not_active");
+}
+
+#[tokio::test]
+async fn test_hold_expiry() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "100.00").await;
+
+    let (_, hold) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/holds",
+        Some(json!({
+            "source_account_id": account_id,
+            "amount": "25.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    let hold_id: uuid::Uuid = hold["id"].as_str().unwrap().parse().unwrap();
+
+    sqlx::query("UPDATE holds SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1")
+        .bind(hold_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let expired = payx_server::workers::hold_expiry::expire_due_holds(&pool)
+        .await
+        .unwrap();
+    assert!(expired >= 1);
+
+    let (_, hold) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/holds/{}", hold_id),
+        None,
+    )
+    .await;
+    assert_eq!(hold["status"], "expired");
+
+    let account = get_account(&router, &api_key, &account_id).await;
+    assert_eq!(account["available_balance"], "100.0000");
+
+    let events: Vec<(String,)> = sqlx::query_as(
+        "SELECT event_type FROM webhook_outbox WHERE business_id = $1::uuid AND event_type = 'hold.expired'",
+    )
+    .bind(&business_id)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(events.len(), 1);
+}
+
+// =============================================================================
+// REVERSAL TESTS
+// =============================================================================
+
+async fn transfer(
+    router: &Router,
+    api_key: &str,
+    source_id: &str,
+    dest_id: &str,
+    amount: &str,
+) -> Value {
+    let (status, txn) = send_json(
+        router,
+        api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "transfer",
+            "source_account_id": source_id,
+            "destination_account_id": dest_id,
+            "amount": amount,
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    txn
+}
+
+#[tokio::test]
+async fn test_full_reversal_of_transfer() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    let txn = transfer(&router, &api_key, &source_id, &dest_id, "40.00").await;
+    let txn_id = txn["id"].as_str().unwrap();
+
+    let (status, reversal) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/transactions/{}/reverse", txn_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(reversal["type"], "reversal");
+    assert_eq!(reversal["amount"], "40.0000");
+    assert_eq!(reversal["reversed_transaction_id"], txn_id);
+    assert_eq!(reversal["source_account_id"], dest_id.as_str());
+    assert_eq!(reversal["destination_account_id"], source_id.as_str());
+
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "100.0000");
+    let dest_balance = get_balance(&router, &api_key, &dest_id).await;
+    assert!(dest_balance == "0.0000" || dest_balance == "0");
+
+    let entries: Vec<(String, String)> = sqlx::query_as(
+        "SELECT account_id::text, entry_type FROM ledger_entries WHERE transaction_id = $1::uuid ORDER BY entry_type",
+    )
+    .bind(reversal["id"].as_str().unwrap())
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            (source_id.clone(), "credit".to_string()),
+            (dest_id.clone(), "debit".to_string())
+        ]
+    );
+
+    let (status, body) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/transactions/{}/reverse", txn_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "validation_error");
+}
+
+#[tokio::test]
+async fn test_partial_refunds_capped_at_original_amount() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    let txn = transfer(&router, &api_key, &source_id, &dest_id, "50.00").await;
+    let uri = format!("/v1/transactions/{}/reverse", txn["id"].as_str().unwrap());
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &uri,
+        Some(json!({ "amount": "20.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &uri,
+        Some(json!({ "amount": "31.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, refund) = send_json(&router, &api_key, "POST", &uri, None).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(refund["amount"], "30.0000");
+
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "100.0000");
+    let dest_balance = get_balance(&router, &api_key, &dest_id).await;
+    assert!(dest_balance == "0.0000" || dest_balance == "0");
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!(
+            "/v1/transactions/{}/reverse",
+            refund["id"].as_str().unwrap()
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+// =============================================================================
+// JOURNAL TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_journal_split_payment() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let payer_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let merchant_id = create_account(&router, &api_key, &business_id, "0.00").await;
+    let fees_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    let (status, txn) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "journal",
+            "amount": "100.00",
+            "currency": "USD",
+            "legs": [
+                { "account_id": payer_id, "entry_type": "debit", "amount": "100.00" },
+                { "account_id": merchant_id, "entry_type": "credit", "amount": "97.00" },
+                { "account_id": fees_id, "entry_type": "credit", "amount": "3.00" }
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(txn["type"], "journal");
+
+    let payer_balance = get_balance(&router, &api_key, &payer_id).await;
+    assert!(payer_balance == "0.0000" || payer_balance == "0");
+    assert_eq!(
+        get_balance(&router, &api_key, &merchant_id).await,
+        "97.0000"
+    );
+    assert_eq!(get_balance(&router, &api_key, &fees_id).await, "3.0000");
+
+    let count: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM ledger_entries WHERE transaction_id = $1::uuid")
+            .bind(txn["id"].as_str().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(count, 3);
+
+    let (status, listed) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/accounts/{}/transactions", fees_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/transactions/{}/reverse", txn["id"].as_str().unwrap()),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(get_balance(&router, &api_key, &payer_id).await, "100.0000");
+    let fees_balance = get_balance(&router, &api_key, &fees_id).await;
+    assert!(fees_balance == "0.0000" || fees_balance == "0");
+}
+
+#[tokio::test]
+async fn test_journal_must_balance() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let payer_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let merchant_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    let (status, body) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "journal",
+            "amount": "50.00",
+            "currency": "USD",
+            "legs": [
+                { "account_id": payer_id, "entry_type": "debit", "amount": "50.00" },
+                { "account_id": merchant_id, "entry_type": "credit", "amount": "49.00" }
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "validation_error");
+
+    let (status, body) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "journal",
+            "amount": "150.00",
+            "currency": "USD",
+            "legs": [
+                { "account_id": payer_id, "entry_type": "debit", "amount": "150.00" },
+                { "account_id": merchant_id, "entry_type": "credit", "amount": "150.00" }
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"]["code"], "insufficient_funds");
+
+    assert_eq!(get_balance(&router, &api_key, &payer_id).await, "100.0000");
+}
+
+// =============================================================================
+// LEDGER TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_list_ledger_entries() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    for amount in ["10.00", "20.00", "30.00"] {
+        let (status, _) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/transactions",
+            Some(json!({
+                "type": "credit",
+                "destination_account_id": account_id,
+                "amount": amount,
+                "currency": "USD"
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, page) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/accounts/{}/ledger?limit=2", account_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let page = page.as_array().unwrap();
+    assert_eq!(page.len(), 2);
+    assert_eq!(page[0]["entry_type"], "credit");
+    assert_eq!(page[0]["amount"], "30.0000");
+    assert_eq!(page[0]["balance_after"], "60.0000");
+    assert_eq!(page[1]["balance_after"], "30.0000");
+    assert!(page[0]["transaction_id"].is_string());
+
+    let (_, next) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!(
+            "/v1/accounts/{}/ledger?limit=2&cursor={}",
+            account_id,
+            page[1]["id"].as_str().unwrap()
+        ),
+        None,
+    )
+    .await;
+    let next = next.as_array().unwrap();
+    assert_eq!(next.len(), 1);
+    assert_eq!(next[0]["balance_after"], "10.0000");
+
+    let (_, filtered) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!(
+            "/v1/accounts/{}/ledger?from=2999-01-01T00:00:00Z",
+            account_id
+        ),
+        None,
+    )
+    .await;
+    assert!(filtered.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_point_in_time_balance() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let account = get_account(&router, &api_key, &account_id).await;
+    let created_at = account["created_at"].as_str().unwrap().to_string();
+
+    for (tx_type, field, amount) in [
+        ("credit", "destination_account_id", "50.00"),
+        ("debit", "source_account_id", "30.00"),
+    ] {
+        let (status, _) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/transactions",
+            Some(json!({
+                "type": tx_type,
+                field: account_id,
+                "amount": amount,
+                "currency": "USD"
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/holds",
+        Some(json!({
+            "source_account_id": account_id,
+            "amount": "20.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (_, entries) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/accounts/{}/ledger", account_id),
+        None,
+    )
+    .await;
+    let entries = entries.as_array().unwrap();
+    let credited_at = entries[1]["created_at"].as_str().unwrap();
+
+    let balance_at = |as_of: String| {
+        let router = router.clone();
+        let api_key = api_key.clone();
+        let uri = format!("/v1/accounts/{}/balance?as_of={}", account_id, as_of);
+        async move { send_json(&router, &api_key, "GET", &uri, None).await }
+    };
+
+    let (status, opening) = balance_at(created_at.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(opening["balance"], "100.0000");
+
+    let (_, after_credit) = balance_at(credited_at.to_string()).await;
+    assert_eq!(after_credit["balance"], "150.0000");
+    assert_eq!(after_credit["available_balance"], "150.0000");
+
+    let (status, current) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/accounts/{}/balance", account_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(current["balance"], "120.0000");
+    assert_eq!(current["available_balance"], "100.0000");
+
+    let (status, _) = balance_at("2000-01-01T00:00:00Z".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+// =============================================================================
+// INTEGRITY TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_integrity_report_detects_drift() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+    let txn = transfer(&router, &api_key, &source_id, &dest_id, "40.00").await;
+
+    let (status, report) = send_json(&router, &api_key, "GET", "/v1/integrity/report", None).await;
+    assert_eq!(status, StatusCode::OK);
+    // The source account's opening balance is posted against settlement
+    assert_eq!(report["accounts_checked"], 3);
+    assert_eq!(report["transactions_checked"], 2);
+    assert!(report["account_drifts"].as_array().unwrap().is_empty());
+    assert!(report["balance_chain_breaks"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+    assert!(report["unbalanced_transactions"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    sqlx::query("UPDATE accounts SET balance = balance + 5 WHERE id = $1::uuid")
+        .bind(&source_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "DELETE FROM ledger_entries WHERE transaction_id = $1::uuid AND entry_type = 'credit'",
+    )
+    .bind(txn["id"].as_str().unwrap())
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (_, report) = send_json(&router, &api_key, "GET", "/v1/integrity/report", None).await;
+    let drifts = report["account_drifts"].as_array().unwrap();
+    assert_eq!(drifts.len(), 2);
+    let source_drift = drifts
+        .iter()
+        .find(|d| d["account_id"] == source_id.as_str())
+        .unwrap();
+    assert_eq!(source_drift["ledger_balance"], "60.0000");
+    assert_eq!(source_drift["stored_balance"], "65.0000");
+    let dest_drift = drifts
+        .iter()
+        .find(|d| d["account_id"] == dest_id.as_str())
+        .unwrap();
+    let ledger_balance = dest_drift["ledger_balance"].as_str().unwrap();
+    assert!(ledger_balance == "0.0000" || ledger_balance == "0");
+    assert_eq!(dest_drift["stored_balance"], "40.0000");
+
+    let unbalanced = report["unbalanced_transactions"].as_array().unwrap();
+    assert_eq!(unbalanced.len(), 1);
+    assert_eq!(unbalanced[0]["transaction_id"], txn["id"]);
+
+    let report = payx_server::workers::integrity_checker::check_and_notify(&pool)
+        .await
+        .unwrap();
+    assert!(!report.is_clean());
+
+    let violation_events = || async {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM webhook_outbox WHERE business_id = $1::uuid AND event_type = 'integrity.violation'",
+        )
+        .bind(&business_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+    };
+    assert_eq!(violation_events().await, 1);
+
+    // Violations already reported are not sent again
+    payx_server::workers::integrity_checker::check_and_notify(&pool)
+        .await
+        .unwrap();
+    assert_eq!(violation_events().await, 1);
+
+    let third_id = create_account(&router, &api_key, &business_id, "10.00").await;
+    sqlx::query("UPDATE accounts SET balance = balance + 1 WHERE id = $1::uuid")
+        .bind(&third_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    payx_server::workers::integrity_checker::check_and_notify(&pool)
+        .await
+        .unwrap();
+    assert_eq!(violation_events().await, 2);
+
+    let payload: Value = sqlx::query_scalar(
+        "SELECT payload FROM webhook_outbox WHERE business_id = $1::uuid AND event_type = 'integrity.violation' ORDER BY created_at DESC LIMIT 1",
+    )
+    .bind(&business_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let drifts = payload["data"]["account_drifts"].as_array().unwrap();
+    assert_eq!(drifts.len(), 1);
+    assert_eq!(drifts[0]["account_id"], third_id.as_str());
+    assert!(payload["data"]["unbalanced_transactions"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_integrity_violations_beyond_the_cap_are_not_renotified() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    // Completed transactions without entries are unbalanced. These ids sort
+    // before any random one, so they fill the report's cap of 100 in order.
+    let txn_id = |n: i64| format!("00000000-0000-4000-8000-{:012x}", n);
+    let remove_unbalanced = || async {
+        sqlx::query("DELETE FROM transactions WHERE id::text LIKE '00000000-0000-4000-8000-%'")
+            .execute(&pool)
+            .await
+            .unwrap();
+    };
+    let insert_unbalanced = |from: i64, to: i64| {
+        let (business_id, account_id, pool) = (&business_id, &account_id, &pool);
+        async move {
+            sqlx::query(
+                r#"
+                INSERT INTO transactions (id, business_id, type, status, destination_account_id, amount, currency)
+                SELECT ('00000000-0000-4000-8000-' || lpad(to_hex(n), 12, '0'))::uuid,
+                    $1::uuid, 'credit', 'completed', $2::uuid, 1, 'USD'
+                FROM generate_series($3::bigint, $4::bigint) n
+                "#,
+            )
+            .bind(business_id)
+            .bind(account_id)
+            .bind(from)
+            .bind(to)
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+    };
+    let violation_events = || async {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM webhook_outbox WHERE business_id = $1::uuid AND event_type = 'integrity.violation'",
+        )
+        .bind(&business_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+    };
+    let check = || payx_server::workers::integrity_checker::check_and_notify(&pool);
+
+    remove_unbalanced().await;
+    insert_unbalanced(1, 101).await;
+    let report = check().await.unwrap();
+    assert_eq!(report.unbalanced_transactions.len(), 100);
+    assert_eq!(violation_events().await, 1);
+
+    // A new violation pushes the 100th out of the report...
+    insert_unbalanced(0, 0).await;
+    let report = check().await.unwrap();
+    assert_eq!(
+        report.unbalanced_transactions[0].transaction_id.to_string(),
+        txn_id(0)
+    );
+    assert_eq!(violation_events().await, 2);
+
+    // ... and it is not reported again when it comes back
+    sqlx::query("UPDATE transactions SET status = 'failed' WHERE id = $1::uuid")
+        .bind(txn_id(0))
+        .execute(&pool)
+        .await
+        .unwrap();
+    let report = check().await.unwrap();
+    assert_eq!(
+        report.unbalanced_transactions[99]
+            .transaction_id
+            .to_string(),
+        txn_id(100)
+    );
+    assert_eq!(violation_events().await, 2);
+
+    remove_unbalanced().await;
+}
+
+#[tokio::test]
+async fn test_integrity_report_detects_broken_balance_chain() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    for _ in 0..3 {
+        let (status, _) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/transactions",
+            Some(json!({
+                "type": "credit",
+                "destination_account_id": account_id,
+                "amount": "10.00",
+                "currency": "USD"
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE ledger_entries SET amount = 15
+        WHERE id = (SELECT id FROM ledger_entries WHERE account_id = $1::uuid ORDER BY seq LIMIT 1 OFFSET 1)
+        "#,
+    )
+    .bind(&account_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (_, report) = send_json(&router, &api_key, "GET", "/v1/integrity/report", None).await;
+    let breaks = report["balance_chain_breaks"].as_array().unwrap();
+    assert_eq!(breaks.len(), 1);
+    assert_eq!(breaks[0]["expected_balance_after"], "25.0000");
+    assert_eq!(breaks[0]["balance_after"], "20.0000");
+}
+
+// =============================================================================
+// HASH CHAIN TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_ledger_hash_chain_detects_tampering() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+    for amount in ["10.00", "20.00", "30.00"] {
+        transfer(&router, &api_key, &source_id, &dest_id, amount).await;
+    }
+
+    let uri = format!("/v1/accounts/{}/ledger/verify", source_id);
+    let (status, verification) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(verification["valid"], true);
+    // Opening balance plus three transfers
+    assert_eq!(verification["entries_checked"], 4);
+    assert!(verification["first_break"].is_null());
+
+    let uri = format!("/v1/accounts/{}/ledger", source_id);
+    let (_, ledger) = send_json(&router, &api_key, "GET", &uri, None).await;
+    let entries = ledger.as_array().unwrap();
+    assert!(entries
+        .iter()
+        .all(|e| e["hash"].as_str().unwrap().len() == 64));
+    // Newest first; tamper with the middle entry
+    let tampered_id = entries[1]["id"].as_str().unwrap();
+
+    sqlx::query("UPDATE ledger_entries SET amount = 2 WHERE id = $1::uuid")
+        .bind(tampered_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let uri = format!("/v1/accounts/{}/ledger/verify", source_id);
+    let (_, verification) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(verification["valid"], false);
+    assert_eq!(verification["entries_checked"], 2);
+    assert_eq!(verification["first_break"]["entry_id"], tampered_id);
+
+    let uri = format!("/v1/accounts/{}/ledger/verify", dest_id);
+    let (_, verification) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(verification["valid"], true);
+}
+
+/// Rebuilds the account's chain with unkeyed SHA-256 hashes and clears its
+/// head, as someone with database access but without the key could, and as
+/// chains were stored before hashes were keyed.
+async fn rehash_unkeyed(pool: &PgPool, account_id: &str) {
+    let entries: Vec<LedgerEntry> =
+        sqlx::query_as("SELECT * FROM ledger_entries WHERE account_id = $1::uuid ORDER BY seq")
+            .bind(account_id)
+            .fetch_all(pool)
+            .await
+            .unwrap();
+
+    let mut prev_hash = GENESIS_HASH.to_string();
+    for entry in entries {
+        let hash = entry.compute_unkeyed_hash(&prev_hash);
+        sqlx::query("UPDATE ledger_entries SET prev_hash = $1, hash = $2 WHERE id = $3")
+            .bind(&prev_hash)
+            .bind(&hash)
+            .bind(entry.id)
+            .execute(pool)
+            .await
+            .unwrap();
+        prev_hash = hash;
+    }
+
+    sqlx::query("UPDATE accounts SET ledger_head = NULL WHERE id = $1::uuid")
+        .bind(account_id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_ledger_hash_chain_detects_rebuilt_chain() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+    transfer(&router, &api_key, &source_id, &dest_id, "10.00").await;
+
+    sqlx::query(
+        "UPDATE ledger_entries SET amount = 1, balance_after = 99 WHERE account_id = $1::uuid AND entry_type = 'debit'",
+    )
+    .bind(&source_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    rehash_unkeyed(&pool, &source_id).await;
+
+    let uri = format!("/v1/accounts/{}/ledger/verify", source_id);
+    let (_, verification) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(verification["valid"], false);
+    assert_eq!(verification["entries_checked"], 0);
+    assert_eq!(
+        verification["first_break"]["reason"],
+        "hash does not match entry contents"
+    );
+}
+
+#[tokio::test]
+async fn test_ledger_hash_chain_detects_truncation() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+    for amount in ["10.00", "20.00"] {
+        transfer(&router, &api_key, &source_id, &dest_id, amount).await;
+    }
+
+    // Drop the newest entry; what is left still chains correctly
+    let removed: i64 = sqlx::query_scalar(
+        "DELETE FROM ledger_entries WHERE id = (SELECT id FROM ledger_entries WHERE account_id = $1::uuid ORDER BY seq DESC LIMIT 1) RETURNING seq",
+    )
+    .bind(&source_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let uri = format!("/v1/accounts/{}/ledger/verify", source_id);
+    let (_, verification) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(verification["valid"], false);
+    assert_eq!(verification["entries_checked"], 2);
+    assert!(verification["first_break"]["seq"].as_i64().unwrap() < removed);
+    assert_eq!(
+        verification["first_break"]["reason"],
+        "chain head does not match the latest entry"
+    );
+
+    // Deleting every entry is caught the same way
+    sqlx::query("DELETE FROM ledger_entries WHERE account_id = $1::uuid")
+        .bind(&source_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (_, verification) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(verification["valid"], false);
+    assert!(verification["first_break"]["entry_id"].is_null());
+}
+
+#[tokio::test]
+async fn test_rekey_ledger_chain() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+    transfer(&router, &api_key, &source_id, &dest_id, "10.00").await;
+    rehash_unkeyed(&pool, &source_id).await;
+
+    // Entries posted after upgrading are keyed and chain onto the old ones
+    transfer(&router, &api_key, &source_id, &dest_id, "20.00").await;
+
+    let uri = format!("/v1/accounts/{}/ledger/verify", source_id);
+    let (_, verification) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(verification["valid"], false);
+
+    let account_id = source_id.parse().unwrap();
+    let rekeyed = ledger::rekey_chain(&pool, account_id).await.unwrap();
+    assert!(rekeyed.valid);
+    assert_eq!(rekeyed.entries_checked, 3);
+
+    let (_, verification) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(verification["valid"], true);
+    assert_eq!(verification["entries_checked"], 3);
+
+    // Rekeying trusts only unkeyed entries that still verify
+    rehash_unkeyed(&pool, &dest_id).await;
+    sqlx::query("UPDATE ledger_entries SET amount = 1 WHERE account_id = $1::uuid")
+        .bind(&dest_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let rekeyed = ledger::rekey_chain(&pool, dest_id.parse().unwrap())
+        .await
+        .unwrap();
+    assert!(!rekeyed.valid);
+    assert_eq!(rekeyed.entries_checked, 0);
+}
+
+#[tokio::test]
+async fn test_ledger_verifies_backfilled_opening_at_seq_zero() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+    transfer(&router, &api_key, &source_id, &dest_id, "10.00").await;
+
+    // The opening backfill puts an opening entry at seq 0 when the
+    // account's first entry had seq 1
+    sqlx::query(
+        r#"
+        UPDATE ledger_entries SET seq = 0
+        WHERE id = (
+            SELECT id FROM ledger_entries WHERE account_id = $1::uuid ORDER BY seq LIMIT 1
+        )
+        "#,
+    )
+    .bind(&source_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let uri = format!("/v1/accounts/{}/ledger/verify", source_id);
+    let (_, verification) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(verification["valid"], true);
+    assert_eq!(verification["entries_checked"], 2);
+
+    let rekeyed = ledger::rekey_chain(&pool, source_id.parse().unwrap())
+        .await
+        .unwrap();
+    assert!(rekeyed.valid);
+    assert_eq!(rekeyed.entries_checked, 2);
+}
+
+// =============================================================================
+// OVERDRAFT TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_overdraft_limit() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let (status, account) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({
+            "business_id": business_id,
+            "currency": "USD",
+            "initial_balance": "100.00",
+            "overdraft_limit": "100.00"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(account["overdraft_limit"], "100.0000");
+    let account_id = account["id"].as_str().unwrap().to_string();
+
+    let debit = |amount: &'static str| {
+        json!({
+            "type": "debit",
+            "source_account_id": account_id,
+            "amount": amount,
+            "currency": "USD"
+        })
+    };
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(debit("150.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let account = get_account(&router, &api_key, &account_id).await;
+    assert_eq!(account["balance"], "-50.0000");
+    assert_eq!(account["available_balance"], "-50.0000");
+
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(debit("60.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"]["code"], "insufficient_funds");
+    assert_eq!(error["error"]["details"]["overdraft_limit"], "100.0000");
+
+    // Already overdrawn: a further debit does not fire the webhook again
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(debit("50.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let events: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM webhook_outbox WHERE business_id = $1::uuid AND event_type = 'account.overdrawn'",
+    )
+    .bind(&business_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(events, 1);
+
+    let uri = format!("/v1/accounts/{}", account_id);
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "PATCH",
+        &uri,
+        Some(json!({ "overdraft_limit": "50.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, account) = send_json(
+        &router,
+        &api_key,
+        "PATCH",
+        &uri,
+        Some(json!({ "overdraft_limit": "250.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(account["overdraft_limit"], "250.0000");
+}
+
+// =============================================================================
+// SETTLEMENT TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_credits_and_debits_post_against_settlement_account() {
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    let (status, credit) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "credit",
+            "destination_account_id": account_id,
+            "amount": "100.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "debit",
+            "source_account_id": account_id,
+            "amount": "30.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (_, hold) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/holds",
+        Some(json!({
+            "source_account_id": account_id,
+            "amount": "10.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    let uri = format!("/v1/holds/{}/capture", hold["id"].as_str().unwrap());
+    let (status, _) = send_json(&router, &api_key, "POST", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, accounts) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        "/v1/accounts?include_settlement=true",
+        None,
+    )
+    .await;
+    let settlement: Vec<&Value> = accounts
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|a| a["is_settlement"] == true)
+        .collect();
+    assert_eq!(settlement.len(), 1);
+    assert_eq!(settlement[0]["account_type"], "asset");
+    assert_eq!(settlement[0]["balance"], "60.0000");
+
+    let entries: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM ledger_entries WHERE transaction_id = $1::uuid")
+            .bind(credit["id"].as_str().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(entries, 2);
+
+    let (_, report) = send_json(&router, &api_key, "GET", "/v1/integrity/report", None).await;
+    assert!(report["account_drifts"].as_array().unwrap().is_empty());
+    assert!(report["unbalanced_transactions"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "debit",
+            "source_account_id": settlement[0]["id"],
+            "amount": "1.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Nor can it be moved through a transfer or a journal leg
+    for (source, dest) in [
+        (&settlement[0]["id"], &json!(account_id)),
+        (&json!(account_id), &settlement[0]["id"]),
+    ] {
+        let (status, _) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/transactions",
+            Some(json!({
+                "type": "transfer",
+                "source_account_id": source,
+                "destination_account_id": dest,
+                "amount": "1.00",
+                "currency": "USD"
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "journal",
+            "amount": "1.00",
+            "currency": "USD",
+            "legs": [
+                { "account_id": settlement[0]["id"], "entry_type": "debit", "amount": "1.00" },
+                { "account_id": account_id, "entry_type": "credit", "amount": "1.00" }
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    assert_eq!(get_balance(&router, &api_key, &account_id).await, "60.0000");
+}
+
+// =============================================================================
+// BATCH TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_batch_atomic_and_independent() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    let item = |key: &str, amount: &str| {
+        json!({
+            "idempotency_key": key,
+            "type": "transfer",
+            "source_account_id": source_id,
+            "destination_account_id": dest_id,
+            "amount": amount,
+            "currency": "USD"
+        })
+    };
+
+    // The last item overdraws, so nothing is posted
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions/batch",
+        Some(json!({
+            "mode": "atomic",
+            "transactions": [item("a1", "40.00"), item("a2", "40.00"), item("a3", "40.00")]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"]["code"], "insufficient_funds");
+    assert_eq!(error["error"]["details"]["index"], 2);
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "100.0000");
+
+    let batch = json!({
+        "mode": "atomic",
+        "transactions": [item("a1", "40.00"), item("a2", "40.00")]
+    });
+    let (status, response) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions/batch",
+        Some(batch.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r["status"] == 201));
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "20.0000");
+
+    // Replaying the batch returns the original transactions
+    let (_, replay) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions/batch",
+        Some(batch),
+    )
+    .await;
+    let replayed = replay["results"].as_array().unwrap();
+    assert!(replayed.iter().all(|r| r["status"] == 200));
+    assert_eq!(
+        replayed[0]["transaction"]["id"],
+        results[0]["transaction"]["id"]
+    );
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "20.0000");
+
+    let (status, response) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions/batch",
+        Some(json!({
+            "mode": "independent",
+            "transactions": [item("i1", "15.00"), item("i2", "15.00"), item("i3", "5.00")]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results[0]["status"], 201);
+    assert_eq!(results[1]["status"], 422);
+    assert_eq!(results[1]["error"]["code"], "insufficient_funds");
+    assert!(results[1]["transaction"].is_null());
+    assert_eq!(results[2]["status"], 201);
+
+    let balance = get_balance(&router, &api_key, &source_id).await;
+    assert!(balance == "0.0000" || balance == "0");
+}
+
+// =============================================================================
+// ACCOUNT STATUS TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_account_freeze_and_close() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let other_id = create_account(&router, &api_key, &business_id, "100.00").await;
+
+    let uri = format!("/v1/accounts/{}/freeze", account_id);
+    let (status, account) = send_json(&router, &api_key, "POST", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(account["status"], "frozen");
+
+    // Frozen accounts can receive but not send
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "transfer",
+            "source_account_id": account_id,
+            "destination_account_id": other_id,
+            "amount": "10.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(error["error"]["code"], "account_not_active");
+
+    transfer(&router, &api_key, &other_id, &account_id, "10.00").await;
+    assert_eq!(
+        get_balance(&router, &api_key, &account_id).await,
+        "110.0000"
+    );
+
+    let (status, error) = send_json(&router, &api_key, "POST", &uri, None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(error["error"]["code"], "invalid_account_transition");
+
+    let uri = format!("/v1/accounts/{}/unfreeze", account_id);
+    let (status, account) = send_json(&router, &api_key, "POST", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(account["status"], "active");
+
+    // Closing requires a zero balance
+    let close_uri = format!("/v1/accounts/{}/close", account_id);
+    let (status, _) = send_json(&router, &api_key, "POST", &close_uri, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    transfer(&router, &api_key, &account_id, &other_id, "110.00").await;
+
+    let (status, account) = send_json(&router, &api_key, "POST", &close_uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(account["status"], "closed");
+    assert!(account["closed_at"].is_string());
+
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "transfer",
+            "source_account_id": other_id,
+            "destination_account_id": account_id,
+            "amount": "10.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(error["error"]["code"], "account_not_active");
+
+    let (status, _) = send_json(&router, &api_key, "POST", &uri, None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+// =============================================================================
+// ACCOUNT REFERENCE TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_account_external_id_lookup() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let (status, account) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({
+            "business_id": business_id,
+            "external_id": "user-42",
+            "name": "Alice's wallet",
+            "metadata": { "tier": "gold" }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(account["external_id"], "user-42");
+    assert_eq!(account["name"], "Alice's wallet");
+    assert_eq!(account["metadata"]["tier"], "gold");
+    create_account(&router, &api_key, &business_id, "0.00").await;
+
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({ "business_id": business_id, "external_id": "user-42" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(error["error"]["code"], "duplicate_external_id");
+
+    let (status, found) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        "/v1/accounts?external_id=user-42",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let found = found.as_array().unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0]["id"], account["id"]);
+
+    let uri = format!("/v1/accounts/{}", account["id"].as_str().unwrap());
+    let (status, updated) = send_json(
+        &router,
+        &api_key,
+        "PATCH",
+        &uri,
+        Some(json!({ "name": "Alice" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["name"], "Alice");
+    assert_eq!(updated["external_id"], "user-42");
+    assert_eq!(updated["metadata"]["tier"], "gold");
+
+    // An explicit null clears a field; omitted fields are kept
+    let (status, cleared) = send_json(
+        &router,
+        &api_key,
+        "PATCH",
+        &uri,
+        Some(json!({ "external_id": null, "metadata": null })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(cleared["external_id"].is_null());
+    assert!(cleared["metadata"].is_null());
+    assert_eq!(cleared["name"], "Alice");
+
+    let (status, cleared) = send_json(
+        &router,
+        &api_key,
+        "PATCH",
+        &uri,
+        Some(json!({ "name": null })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(cleared["name"].is_null());
+
+    // The cleared external ID is free for another account
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({ "business_id": business_id, "external_id": "user-42" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+// =============================================================================
+// ACCOUNT HIERARCHY TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_account_hierarchy_rollup() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let master_id = create_account(&router, &api_key, &business_id, "100.00").await;
+
+    let mut wallets = Vec::new();
+    for balance in ["25.00", "50.00"] {
+        let (status, wallet) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/accounts",
+            Some(json!({
+                "business_id": business_id,
+                "initial_balance": balance,
+                "parent_account_id": master_id
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(wallet["parent_account_id"], master_id.as_str());
+        wallets.push(wallet["id"].as_str().unwrap().to_string());
+    }
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({
+            "business_id": business_id,
+            "currency": "EUR",
+            "initial_balance": "10.00",
+            "parent_account_id": wallets[0]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (other_business, _) = create_business(&router).await;
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({ "business_id": other_business, "parent_account_id": master_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "transfer",
+            "source_account_id": wallets[1],
+            "destination_account_id": master_id,
+            "amount": "20.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, rollup) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/accounts/{}/rollup", master_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(rollup["normal_balance"], "credit");
+    let balances = rollup["balances"].as_array().unwrap();
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balances[0]["currency"], "EUR");
+    assert_eq!(balances[0]["balance"], "10.0000");
+    assert_eq!(balances[1]["currency"], "USD");
+    assert_eq!(balances[1]["balance"], "175.0000");
+    assert_eq!(balances[1]["accounts"], 3);
+
+    let (status, children) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/accounts?parent_account_id={}", master_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(children.as_array().unwrap().len(), 2);
+
+    // A debit-normal descendant counts against a credit-normal total
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({
+            "business_id": business_id,
+            "account_type": "asset",
+            "initial_balance": "30.00",
+            "parent_account_id": master_id
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (_, rollup) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/accounts/{}/rollup", master_id),
+        None,
+    )
+    .await;
+    assert_eq!(rollup["balances"][1]["balance"], "145.0000");
+    assert_eq!(rollup["balances"][1]["available_balance"], "145.0000");
+    assert_eq!(rollup["balances"][1]["accounts"], 4);
+
+    let empty_parent = create_account(&router, &api_key, &business_id, "0.00").await;
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({ "business_id": business_id, "parent_account_id": empty_parent })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/accounts/{}/close", empty_parent),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("sub-accounts"));
+}
+
+// =============================================================================
+// VELOCITY LIMIT TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_velocity_limits() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "1000.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+    let uri = format!("/v1/accounts/{}/limits", source_id);
+
+    let (status, limits) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(limits["daily_debit_count"].is_null());
+
+    let (status, limits) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &uri,
+        Some(json!({
+            "max_transaction_amount": "100.00",
+            "daily_debit_count": 3,
+            "daily_debit_amount": "150.00"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(limits["daily_debit_count"], 3);
+    assert!(limits["monthly_debit_amount"].is_null());
+
+    let debit = |amount: &str| {
+        json!({
+            "type": "debit",
+            "source_account_id": source_id,
+            "amount": amount,
+            "currency": "USD"
+        })
+    };
+
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(debit("120.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"]["code"], "limit_exceeded");
+    assert_eq!(error["error"]["details"]["limit"], "max_transaction_amount");
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(debit("60.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "transfer",
+            "source_account_id": source_id,
+            "destination_account_id": dest_id,
+            "amount": "60.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(debit("40.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"]["details"]["limit"], "daily_debit_amount");
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(debit("10.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(debit("5.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"]["details"]["limit"], "daily_debit_count");
+
+    let (status, _) = send_json(&router, &api_key, "PUT", &uri, Some(json!({}))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(debit("500.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_velocity_limits_on_journals() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "1000.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+
+    let uri = format!("/v1/accounts/{}/limits", source_id);
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &uri,
+        Some(json!({
+            "max_transaction_amount": "80.00",
+            "daily_debit_amount": "100.00"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let journal = |debits: &[&str]| {
+        let total: f64 = debits.iter().map(|d| d.parse::<f64>().unwrap()).sum();
+        let total = format!("{:.2}", total);
+        let mut legs: Vec<Value> = debits
+            .iter()
+            .map(|d| json!({ "account_id": source_id, "entry_type": "debit", "amount": d }))
+            .collect();
+        legs.push(json!({ "account_id": dest_id, "entry_type": "credit", "amount": total }));
+        json!({ "type": "journal", "amount": total, "currency": "USD", "legs": legs })
+    };
+
+    // Legs on the same account are checked together
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(journal(&["50.00", "40.00"])),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"]["details"]["limit"], "max_transaction_amount");
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(journal(&["60.00"])),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(journal(&["50.00"])),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"]["details"]["limit"], "daily_debit_amount");
+    assert_eq!(error["error"]["details"]["attempted"], "110.0000");
+}
+
+#[tokio::test]
+async fn test_velocity_limits_on_holds() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "1000.00").await;
+
+    let uri = format!("/v1/accounts/{}/limits", account_id);
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &uri,
+        Some(json!({ "daily_debit_amount": "100.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let hold = |amount: &str| {
+        json!({
+            "source_account_id": account_id,
+            "amount": amount,
+            "currency": "USD"
+        })
+    };
+
+    let (status, placed) =
+        send_json(&router, &api_key, "POST", "/v1/holds", Some(hold("70.00"))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let hold_id = placed["id"].as_str().unwrap().to_string();
+
+    // The open hold counts toward the window
+    let (status, error) =
+        send_json(&router, &api_key, "POST", "/v1/holds", Some(hold("40.00"))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"]["details"]["limit"], "daily_debit_amount");
+
+    // Capture is checked again, against the limits in force when it happens
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &uri,
+        Some(json!({ "daily_debit_amount": "50.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let capture_uri = format!("/v1/holds/{}/capture", hold_id);
+    let (status, error) = send_json(&router, &api_key, "POST", &capture_uri, None).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"]["details"]["limit"], "daily_debit_amount");
+
+    let (status, captured) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &capture_uri,
+        Some(json!({ "amount": "50.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(captured["status"], "captured");
+}
+
+#[tokio::test]
+async fn test_velocity_limits_count_fees() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "1000.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0.00").await;
+    let (_, revenue) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({ "business_id": business_id, "account_type": "revenue" })),
+    )
+    .await;
+
+    for transaction_type in ["debit", "transfer"] {
+        let (status, _) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/fee_rules",
+            Some(json!({
+                "transaction_type": transaction_type,
+                "currency": "USD",
+                "flat_amount": "1.00",
+                "revenue_account_id": revenue["id"]
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &format!("/v1/accounts/{}/limits", source_id),
+        Some(json!({ "daily_debit_amount": "100.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 100.00 fits the limit on its own, but not with its 1.00 fee
+    for body in [
+        json!({
+            "type": "debit",
+            "source_account_id": source_id,
+            "amount": "100.00",
+            "currency": "USD"
+        }),
+        json!({
+            "type": "transfer",
+            "source_account_id": source_id,
+            "destination_account_id": dest_id,
+            "amount": "100.00",
+            "currency": "USD"
+        }),
+    ] {
+        let (status, error) =
+            send_json(&router, &api_key, "POST", "/v1/transactions", Some(body)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error["error"]["details"]["limit"], "daily_debit_amount");
+        assert_eq!(error["error"]["details"]["attempted"], "101.0000");
+    }
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "transfer",
+            "source_account_id": source_id,
+            "destination_account_id": dest_id,
+            "amount": "99.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "900.0000");
+}
+
+// =============================================================================
+// ACCOUNT CLASSIFICATION TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_account_types_follow_normal_balance() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+
+    let mut accounts = Vec::new();
+    for account_type in ["asset", "equity", "expense"] {
+        let (status, account) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/accounts",
+            Some(json!({ "business_id": business_id, "account_type": account_type })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        accounts.push(account);
+    }
+    let (cash, capital, rent) = (&accounts[0], &accounts[1], &accounts[2]);
+    assert_eq!(cash["normal_balance"], "debit");
+    assert_eq!(capital["normal_balance"], "credit");
+
+    let journal = |legs: Value, amount: &str| {
+        json!({
+            "type": "journal",
+            "amount": amount,
+            "currency": "USD",
+            "legs": legs
+        })
+    };
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(journal(
+            json!([
+                { "account_id": cash["id"], "entry_type": "debit", "amount": "1000.00" },
+                { "account_id": capital["id"], "entry_type": "credit", "amount": "1000.00" }
+            ]),
+            "1000.00",
+        )),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(journal(
+            json!([
+                { "account_id": rent["id"], "entry_type": "debit", "amount": "200.00" },
+                { "account_id": cash["id"], "entry_type": "credit", "amount": "200.00" }
+            ]),
+            "200.00",
+        )),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    for (account, balance) in [
+        (cash, "800.0000"),
+        (capital, "1000.0000"),
+        (rent, "200.0000"),
+    ] {
+        let uri = format!("/v1/accounts/{}", account["id"].as_str().unwrap());
+        let (_, account) = send_json(&router, &api_key, "GET", &uri, None).await;
+        assert_eq!(account["balance"], balance);
+    }
+
+    // Crediting an asset reduces it, so it cannot go below zero
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(journal(
+            json!([
+                { "account_id": rent["id"], "entry_type": "debit", "amount": "900.00" },
+                { "account_id": cash["id"], "entry_type": "credit", "amount": "900.00" }
+            ]),
+            "900.00",
+        )),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error["error"]["code"], "insufficient_funds");
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/holds",
+        Some(json!({
+            "source_account_id": cash["id"],
+            "amount": "10.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Types from before classification still create liabilities
+    for legacy in ["checking", "savings"] {
+        let (status, account) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/accounts",
+            Some(json!({ "business_id": business_id, "account_type": legacy })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(account["account_type"], "liability");
+        assert_eq!(account["normal_balance"], "credit");
+    }
+
+    for unknown in ["settlement", "Asset", "expnse"] {
+        let (status, _) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/accounts",
+            Some(json!({ "business_id": business_id, "account_type": unknown })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let (_, report) = send_json(&router, &api_key, "GET", "/v1/integrity/report", None).await;
+    assert!(report["account_drifts"].as_array().unwrap().is_empty());
+    assert!(report["balance_chain_breaks"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_frozen_asset_account_cannot_be_credited() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let mut accounts = Vec::new();
+    for account_type in ["asset", "equity"] {
+        let (_, account) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/accounts",
+            Some(json!({ "business_id": business_id, "account_type": account_type })),
+        )
+        .await;
+        accounts.push(account["id"].as_str().unwrap().to_string());
+    }
+    let (cash, capital) = (&accounts[0], &accounts[1]);
+
+    let journal = |debit: &str, credit: &str, amount: &str| {
+        json!({
+            "type": "journal",
+            "amount": amount,
+            "currency": "USD",
+            "legs": [
+                { "account_id": debit, "entry_type": "debit", "amount": amount },
+                { "account_id": credit, "entry_type": "credit", "amount": amount }
+            ]
+        })
+    };
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(journal(cash, capital, "100.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/accounts/{}/freeze", cash),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // A credit would drain the debit-normal account
+    let (status, error) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(journal(capital, cash, "40.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(error["error"]["code"], "account_not_active");
+
+    // Funds can still come in
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(journal(cash, capital, "10.00")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(get_balance(&router, &api_key, cash).await, "110.0000");
+}
+
+// =============================================================================
+// INTEREST TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_interest_accrues_daily_and_posts_monthly() {
+    use chrono::{Datelike, Days, Months, NaiveTime, Utc};
+    use payx_server::workers::interest_accrual::run_interest;
+    use rust_decimal::Decimal;
+
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let savings_id = create_account(&router, &api_key, &business_id, "1000.00").await;
+    let (_, expense) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({ "business_id": business_id, "account_type": "expense" })),
+    )
+    .await;
+    let uri = format!("/v1/accounts/{}/interest", savings_id);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &uri,
+        Some(json!({ "annual_rate": "0.0365", "expense_account_id": savings_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, interest) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &uri,
+        Some(json!({ "annual_rate": "0.0365", "expense_account_id": expense["id"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(interest["annual_rate"], "0.036500");
+
+    // 1000 at 3.65% accrues exactly 0.1 a day
+    let today = Utc::now().date_naive();
+    let next_month = (today + Months::new(1)).with_day(1).unwrap();
+    let days = (next_month - today).num_days();
+    let midnight = (next_month + Days::new(1))
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+
+    // The last day only accrues once late commits have had time to land
+    let run = run_interest(&pool, midnight).await.unwrap();
+    assert_eq!(run.accrued as i64, days);
+    assert_eq!(run.posted, 1);
+
+    let at = midnight + ledger::DAY_CLOSE_GRACE;
+    let run = run_interest(&pool, at).await.unwrap();
+    assert_eq!((run.accrued, run.posted), (1, 0));
+
+    let run = run_interest(&pool, at).await.unwrap();
+    assert_eq!((run.accrued, run.posted), (0, 0));
+
+    let (status, accruals) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("{}/accruals?limit=100", uri),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let accruals = accruals.as_array().unwrap();
+    assert_eq!(accruals.len() as i64, days + 1);
+    assert!(accruals[0]["transaction_id"].is_null());
+    assert_eq!(accruals[1]["amount"], "0.10000000");
+    assert!(accruals[1]["transaction_id"].is_string());
+
+    let expected = Decimal::new(1000, 0) + Decimal::new(days, 1);
+    let (_, savings) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/accounts/{}", savings_id),
+        None,
+    )
+    .await;
+    assert_eq!(savings["balance"], format!("{:.4}", expected));
+
+    let (_, expense) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/accounts/{}", expense["id"].as_str().unwrap()),
+        None,
+    )
+    .await;
+    assert_eq!(expense["balance"], format!("{:.4}", Decimal::new(days, 1)));
+}
+
+#[tokio::test]
+async fn test_interest_follows_business_timezone_and_skips_inactive_accounts() {
+    use chrono::{Datelike, Days, Months, NaiveDate, NaiveTime, Utc};
+    use payx_server::workers::interest_accrual::run_interest;
+
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &format!("/v1/businesses/{}", business_id),
+        Some(json!({ "timezone": "Pacific/Kiritimati" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let savings_id = create_account(&router, &api_key, &business_id, "1000.00").await;
+    let (_, expense) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({ "business_id": business_id, "account_type": "expense" })),
+    )
+    .await;
+    let expense_id = expense["id"].as_str().unwrap();
+    let uri = format!("/v1/accounts/{}/interest", savings_id);
+    let (status, interest) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &uri,
+        Some(json!({ "annual_rate": "0.0365", "expense_account_id": expense_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Accrual starts on the business's day, not UTC's
+    let local_today = Utc::now()
+        .with_timezone(&chrono_tz::Pacific::Kiritimati)
+        .date_naive();
+    assert_eq!(
+        interest["accrued_through"],
+        (local_today - Days::new(1)).to_string()
+    );
+
+    // Noon UTC is already the next day at UTC+14, so two days have ended
+    // there and only one in UTC
+    let day = Utc::now().date_naive() + Days::new(5);
+    sqlx::query("UPDATE account_interest SET accrued_through = $1 WHERE account_id = $2::uuid")
+        .bind(day)
+        .bind(&savings_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let noon = |date: NaiveDate| {
+        date.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap())
+            .and_utc()
+    };
+
+    let freeze = |id: &str, action: &str| format!("/v1/accounts/{}/{}", id, action);
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &freeze(&savings_id, "freeze"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let run = run_interest(&pool, noon(day + Days::new(2))).await.unwrap();
+    assert_eq!((run.accrued, run.posted), (0, 0));
+    send_json(
+        &router,
+        &api_key,
+        "POST",
+        &freeze(&savings_id, "unfreeze"),
+        None,
+    )
+    .await;
+
+    let run = run_interest(&pool, noon(day + Days::new(2))).await.unwrap();
+    assert_eq!(run.accrued, 2);
+    let (_, accruals) =
+        send_json(&router, &api_key, "GET", &format!("{}/accruals", uri), None).await;
+    let dates: Vec<&str> = accruals
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["accrual_date"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        dates,
+        [
+            (day + Days::new(2)).to_string(),
+            (day + Days::new(1)).to_string()
+        ]
+    );
+
+    // A frozen expense account holds posting back without failing the run
+    let month_start = (day + Days::new(2) + Months::new(1)).with_day(1).unwrap();
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &freeze(expense_id, "freeze"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let run = run_interest(&pool, noon(month_start)).await.unwrap();
+    assert!(run.accrued > 0);
+    assert_eq!(run.posted, 0);
+
+    send_json(
+        &router,
+        &api_key,
+        "POST",
+        &freeze(expense_id, "unfreeze"),
+        None,
+    )
+    .await;
+    let run = run_interest(&pool, noon(month_start)).await.unwrap();
+    assert_eq!((run.accrued, run.posted), (0, 1));
+
+    let transaction_id: uuid::Uuid = sqlx::query_scalar(
+        "SELECT transaction_id FROM interest_accruals WHERE account_id = $1::uuid AND accrual_date = $2",
+    )
+    .bind(&savings_id)
+    .bind(day + Days::new(1))
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let (_, txn) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/transactions/{}", transaction_id),
+        None,
+    )
+    .await;
+    assert_eq!(txn["type"], "interest");
+    assert_eq!(txn["source_account_id"], expense_id);
+    assert_eq!(txn["destination_account_id"], savings_id.as_str());
+}
+
+// =============================================================================
+// FEE TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_transfer_fees_post_to_revenue_account() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0").await;
+    let (_, revenue) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({ "business_id": business_id, "account_type": "revenue" })),
+    )
+    .await;
+    let revenue_id = revenue["id"].as_str().unwrap();
+
+    let rule = json!({
+        "transaction_type": "transfer",
+        "currency": "USD",
+        "flat_amount": "0.50",
+        "percentage": "1.5",
+        "min_amount": "1.00",
+        "max_amount": "2.00",
+        "revenue_account_id": dest_id
+    });
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/fee_rules",
+        Some(rule.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let mut rule = rule;
+    rule["revenue_account_id"] = json!(revenue_id);
+    let (status, created) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/fee_rules",
+        Some(rule.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = send_json(&router, &api_key, "POST", "/v1/fee_rules", Some(rule)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 0.50 + 1.5% of 40 = 1.10
+    let txn = transfer(&router, &api_key, &source_id, &dest_id, "40.00").await;
+    let fees = txn["fees"].as_array().unwrap();
+    assert_eq!(fees.len(), 1);
+    assert_eq!(fees[0]["fee_rule_id"], created["id"]);
+    assert_eq!(fees[0]["payer_account_id"], source_id);
+    assert_eq!(fees[0]["flat_amount"], "0.5000");
+    assert_eq!(fees[0]["percentage_amount"], "0.6000");
+    assert_eq!(fees[0]["amount"], "1.1000");
+
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "58.9000");
+    assert_eq!(get_balance(&router, &api_key, &dest_id).await, "40.0000");
+    assert_eq!(get_balance(&router, &api_key, revenue_id).await, "1.1000");
+
+    // The fee counts against the source's funds
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "transfer",
+            "source_account_id": source_id,
+            "destination_account_id": dest_id,
+            "amount": "58.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // 0.50 + 0.15 is raised to the minimum
+    let txn = transfer(&router, &api_key, &source_id, &dest_id, "10.00").await;
+    assert_eq!(txn["fees"][0]["amount"], "1.0000");
+
+    let (_, fetched) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("/v1/transactions/{}", txn["id"].as_str().unwrap()),
+        None,
+    )
+    .await;
+    assert_eq!(fetched["fees"], txn["fees"]);
+
+    // A full reversal refunds the fee too
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &format!("/v1/transactions/{}/reverse", txn["id"].as_str().unwrap()),
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "58.9000");
+    assert_eq!(get_balance(&router, &api_key, revenue_id).await, "1.1000");
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "DELETE",
+        &format!("/v1/fee_rules/{}", created["id"].as_str().unwrap()),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let txn = transfer(&router, &api_key, &source_id, &dest_id, "10.00").await;
+    assert!(txn["fees"].as_array().unwrap().is_empty());
+
+    let (_, report) = send_json(&router, &api_key, "GET", "/v1/integrity/report", None).await;
+    assert!(report["account_drifts"].as_array().unwrap().is_empty());
+    assert!(report["unbalanced_transactions"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_partial_refunds_return_fees_exactly() {
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let source_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let dest_id = create_account(&router, &api_key, &business_id, "0").await;
+    let (_, revenue) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/accounts",
+        Some(json!({ "business_id": business_id, "account_type": "revenue" })),
+    )
+    .await;
+    let revenue_id = revenue["id"].as_str().unwrap();
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/fee_rules",
+        Some(json!({
+            "transaction_type": "transfer",
+            "currency": "USD",
+            "flat_amount": "0.0003",
+            "revenue_account_id": revenue_id
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let txn = transfer(&router, &api_key, &source_id, &dest_id, "10.00").await;
+    assert_eq!(txn["fees"][0]["amount"], "0.0003");
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "89.9997");
+    let uri = format!("/v1/transactions/{}/reverse", txn["id"].as_str().unwrap());
+
+    // Half of 0.0003 rounds to 0.0002 on the first refund
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &uri,
+        Some(json!({ "amount": "5.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(get_balance(&router, &api_key, revenue_id).await, "0.0001");
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "94.9999");
+
+    // ... so the second refund only gives back what is left of the fee
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        &uri,
+        Some(json!({ "amount": "5.00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(get_balance(&router, &api_key, &source_id).await, "100.0000");
+    for account_id in [revenue_id, dest_id.as_str()] {
+        let balance = get_balance(&router, &api_key, account_id).await;
+        assert!(balance == "0" || balance == "0.0000");
+    }
+
+    let (_, report) = send_json(&router, &api_key, "GET", "/v1/integrity/report", None).await;
+    assert!(report["account_drifts"].as_array().unwrap().is_empty());
+    assert!(report["unbalanced_transactions"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
+// =============================================================================
+// BALANCE SNAPSHOT TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_daily_balances_close_in_business_timezone() {
+    use chrono::{Days, Utc};
+    use payx_server::workers::balance_snapshot::run_snapshots;
+
+    let (router, pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let uri = format!("/v1/businesses/{}", business_id);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &uri,
+        Some(json!({ "timezone": "Mars/Olympus_Mons" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, business) = send_json(
+        &router,
+        &api_key,
+        "PUT",
+        &uri,
+        Some(json!({ "timezone": "Pacific/Kiritimati" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(business["timezone"], "Pacific/Kiritimati");
+
+    let account_id = create_account(&router, &api_key, &business_id, "100.00").await;
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "POST",
+        "/v1/transactions",
+        Some(json!({
+            "type": "credit",
+            "destination_account_id": account_id,
+            "amount": "50.00",
+            "currency": "USD"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // UTC+14 is usually a calendar day ahead of UTC
+    let now = Utc::now();
+    let today = now
+        .with_timezone(&chrono_tz::Pacific::Kiritimati)
+        .date_naive();
+    let uri = format!("/v1/accounts/{}/balances/daily", account_id);
+
+    // A day is only snapshotted once late commits have had time to land
+    let today_end = ledger::day_start(chrono_tz::Pacific::Kiritimati, today + Days::new(1));
+    run_snapshots(&pool, today_end).await.unwrap();
+    let (_, snapshots) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert!(snapshots.as_array().unwrap().is_empty());
+
+    run_snapshots(&pool, today_end + ledger::DAY_CLOSE_GRACE)
+        .await
+        .unwrap();
+    let (_, snapshots) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(snapshots.as_array().unwrap().len(), 1);
+
+    let later = now + Days::new(2) + ledger::DAY_CLOSE_GRACE;
+    run_snapshots(&pool, later).await.unwrap();
+    run_snapshots(&pool, later).await.unwrap();
+
+    let (status, snapshots) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let snapshots = snapshots.as_array().unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(snapshots[0]["snapshot_date"], today.to_string());
+    assert_eq!(
+        snapshots[1]["snapshot_date"],
+        (today + Days::new(1)).to_string()
+    );
+    assert!(snapshots.iter().all(|s| s["balance"] == "150.0000"));
+
+    let tomorrow = today + Days::new(1);
+    let (_, snapshots) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("{}?from={}", uri, tomorrow),
+        None,
+    )
+    .await;
+    assert_eq!(snapshots.as_array().unwrap().len(), 1);
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!("{}?from={}&to={}", uri, tomorrow, today),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+// =============================================================================
+// STATEMENT TESTS
+// =============================================================================
+
+#[tokio::test]
+async fn test_account_statement_json_and_csv() {
+    use chrono::{Days, Utc};
+
+    let (router, _pool) = setup().await;
+
+    let (business_id, api_key) = create_business(&router).await;
+    let account_id = create_account(&router, &api_key, &business_id, "100.00").await;
+
+    for (tx_type, side, amount, description) in [
+        (
+            "credit",
+            "destination_account_id",
+            "50.00",
+            "Deposit, \"March\"",
+        ),
+        ("debit", "source_account_id", "20.00", "Withdrawal"),
+    ] {
+        let (status, _) = send_json(
+            &router,
+            &api_key,
+            "POST",
+            "/v1/transactions",
+            Some(json!({
+                "type": tx_type,
+                side: account_id,
+                "amount": amount,
+                "currency": "USD",
+                "description": description
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let today = Utc::now().date_naive();
+    let uri = format!(
+        "/v1/accounts/{}/statement?from={}&to={}",
+        account_id, today, today
+    );
+    let (status, statement) = send_json(&router, &api_key, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(statement["timezone"], "UTC");
+    assert_eq!(statement["opening_balance"], "0.0000");
+    assert_eq!(statement["total_credits"], "150.0000");
+    assert_eq!(statement["total_debits"], "20.0000");
+    assert_eq!(statement["closing_balance"], "130.0000");
+
+    let lines = statement["lines"].as_array().unwrap();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["transaction_type"], "opening");
+    assert_eq!(lines[0]["balance"], "100.0000");
+    assert_eq!(lines[1]["transaction_type"], "credit");
+    assert_eq!(lines[1]["balance"], "150.0000");
+    assert_eq!(lines[2]["entry_type"], "debit");
+    assert_eq!(lines[2]["balance"], "130.0000");
+
+    let yesterday = today - Days::new(1);
+    let (_, earlier) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!(
+            "/v1/accounts/{}/statement?from={}&to={}",
+            account_id, yesterday, yesterday
+        ),
+        None,
+    )
+    .await;
+    assert!(earlier["lines"].as_array().unwrap().is_empty());
+    assert_eq!(earlier["closing_balance"], "0.0000");
+
+    let (status, _) = send_json(
+        &router,
+        &api_key,
+        "GET",
+        &format!(
+            "/v1/accounts/{}/statement?from={}&to={}",
+            account_id, today, yesterday
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let res = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("{}&format=csv", uri))
+                .header("authorization", format!("Bearer {}", api_key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "text/csv; charset=utf-8");
+
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let csv = String::from_utf8(body.to_vec()).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows.len(), 8);
+    assert!(rows[0].starts_with("date,entry_id,"));
+    assert_eq!(rows[1], format!("{},,,,Opening balance,,,0.0000", today));
+    assert!(rows[2].contains(",opening,Opening balance,credit,100.0000,100.0000"));
+    assert!(rows[3].contains(",credit,\"Deposit, \"\"March\"\"\",credit,50.0000,150.0000"));
+    assert_eq!(rows[7], format!("{},,,,Closing balance,,,130.0000", today));
+}
+
+#[test]
+fn test_statement_csv_neutralises_formulas() {
+    use chrono::Utc;
+    use payx_server::domain::{AccountStatement, EntryType, StatementLine, TransactionType};
+    use rust_decimal::Decimal;
+
+    let line = |description: &str| StatementLine {
+        entry_id: uuid::Uuid::new_v4(),
+        transaction_id: uuid::Uuid::new_v4(),
+        transaction_type: TransactionType::Credit,
+        description: Some(description.to_string()),
+        entry_type: EntryType::Credit,
+        amount: Decimal::ONE,
+        balance: Decimal::ONE,
+        created_at: Utc::now(),
+    };
+    let today = Utc::now().date_naive();
+    let statement = AccountStatement {
+        account_id: uuid::Uuid::new_v4(),
+        currency: "USD".to_string(),
+        timezone: "UTC".to_string(),
+        from: today,
+        to: today,
+        opening_balance: Decimal::ZERO,
+        total_credits: Decimal::ZERO,
+        total_debits: Decimal::ZERO,
+        closing_balance: Decimal::ZERO,
+        lines: vec![
+            line("=HYPERLINK(\"http://evil.example\",\"x\")"),
+            line("+1"),
+            line("-2+3"),
+            line("@SUM(A1)"),
+            line("\tindented"),
+            line("\rreturn"),
+            line("Refund - March"),
+        ],
+    };
+
+    let csv = statement.to_csv();
+    let descriptions: Vec<&str> = csv
+        .lines()
+        .skip(2)
+        .take(6)
+        .map(|row| row.splitn(5, ',').nth(4).unwrap())
+        .collect();
+    assert!(descriptions[0].starts_with("\"'=HYPERLINK(\"\"http://evil.example\"\""));
+    assert!(descriptions[1].starts_with("'+1,"));
+    assert!(descriptions[2].starts_with("'-2+3,"));
+    assert!(descriptions[3].starts_with("'@SUM(A1),"));
+    assert!(descriptions[4].starts_with("'\tindented,"));
+    // A leading CR is prefixed and, being a line break, quoted
+    assert!(descriptions[5].starts_with("\"'"));
+    assert!(csv.contains(",Refund - March,"));
+}